@@ -1,5 +1,11 @@
-use crate::{GameState, Piece};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::{random_seed, EvalWeights, GameState, HandMove, OpponentModel, OpponentStyle, OrientedMove, Piece, Topology};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub enum AIDifficulty {
@@ -9,23 +15,232 @@ pub enum AIDifficulty {
     Expert,
 }
 
+impl AIDifficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(AIDifficulty::Easy),
+            "medium" => Some(AIDifficulty::Medium),
+            "hard" => Some(AIDifficulty::Hard),
+            "expert" => Some(AIDifficulty::Expert),
+            _ => None,
+        }
+    }
+}
+
+/// What an AI considered when picking a move. Coordinates are `(x, y)`.
+#[derive(Debug, Clone, Default)]
+pub struct SearchAnalysis {
+    pub best_move: Option<(usize, usize)>,
+    /// Every searched move with its score, best first. Under a time limit
+    /// this is only the moves whose search finished; see `unsearched`.
+    pub move_scores: Vec<((usize, usize), i32)>,
+    /// Legal moves the time limit left without a score.
+    pub unsearched: Vec<(usize, usize)>,
+    /// Expected line of play starting with `best_move`, alternating sides.
+    pub principal_variation: Vec<(usize, usize)>,
+    /// In the hand variant, the chosen hand move and the hand move behind
+    /// each entry of `move_scores`, in the same order.
+    pub best_hand_move: Option<HandMove>,
+    pub hand_moves: Vec<HandMove>,
+    pub nodes: u64,
+    pub depth: u32,
+    pub elapsed: Duration,
+}
+
+impl SearchAnalysis {
+    /// Human-readable summary listing at most `max_moves` candidates.
+    /// Moves are shown as `row col`, like the rest of the interface.
+    pub fn report(&self, max_moves: usize) -> String {
+        let mut result = String::new();
+
+        match (self.best_hand_move, self.best_move) {
+            (Some(hand_move), _) => result.push_str(&format!("Best move: {}\n", describe_hand_move(hand_move))),
+            (None, Some((x, y))) => result.push_str(&format!("Best move: {} {}\n", y, x)),
+            (None, None) => result.push_str("Best move: none\n"),
+        }
+        result.push_str(&format!(
+            "Depth {} | {} nodes | {:.2}ms\n",
+            self.depth,
+            self.nodes,
+            self.elapsed.as_secs_f64() * 1000.0
+        ));
+
+        if !self.principal_variation.is_empty() {
+            let line: Vec<String> = self
+                .principal_variation
+                .iter()
+                .map(|(x, y)| format!("({} {})", y, x))
+                .collect();
+            result.push_str(&format!("Principal variation: {}\n", line.join(" -> ")));
+        }
+
+        if !self.move_scores.is_empty() {
+            result.push_str(&format!("Candidates ({} total):\n", self.move_scores.len()));
+            for (index, ((x, y), score)) in self.move_scores.iter().enumerate().take(max_moves) {
+                match self.hand_moves.get(index) {
+                    Some(&hand_move) => result.push_str(&format!("  {:<28} {:>8}\n", describe_hand_move(hand_move), score)),
+                    None => result.push_str(&format!("  {:3} {:3}  {:>8}\n", y, x, score)),
+                }
+            }
+        }
+        if !self.unsearched.is_empty() {
+            result.push_str(&format!("Not searched before the time limit: {} moves\n", self.unsearched.len()));
+        }
+
+        result
+    }
+}
+
+/// `piece <n> <row> <col>`, with the orientation when the piece is turned.
+fn describe_hand_move(hand_move: HandMove) -> String {
+    let mut text = format!("piece {} {} {}", hand_move.piece_index + 1, hand_move.y, hand_move.x);
+    if hand_move.orientation != 0 {
+        text.push_str(&format!(" orientation {}", hand_move.orientation));
+    }
+    text
+}
+
 pub trait AIPlayer {
     fn choose_move(&mut self, game: &GameState, piece: &Piece) -> Option<(usize, usize)>;
     fn get_name(&self) -> &str;
+
+    /// Starts thinking on the opponent's time. `game` is the position right
+    /// after our move and `piece` the piece we just placed. AIs that cannot
+    /// ponder ignore this.
+    fn start_pondering(&mut self, _game: &GameState, _piece: &Piece) {}
+
+    /// Cancels any background search started by `start_pondering`.
+    fn stop_pondering(&mut self) {}
+
+    /// Called with the positions before and after each opponent move, and
+    /// the piece it was given when known. AIs without an opponent model
+    /// ignore this.
+    fn observe_opponent_move(&mut self, _before: &GameState, _after: &GameState, _piece: Option<&Piece>) {}
+
+    /// The opponent's style as inferred so far, if this AI models it.
+    fn opponent_style(&self) -> Option<OpponentStyle> {
+        None
+    }
+
+    /// Chooses a piece from the current player's hand, its orientation and
+    /// where to place it. By default each distinct piece and orientation is
+    /// analysed on its own and the best score wins; AIs without scores take
+    /// the first that fits.
+    fn choose_hand_move(&mut self, game: &GameState) -> Option<HandMove> {
+        let mut choices = Vec::new();
+        let mut pieces = Vec::new();
+        for (piece_index, piece) in game.hand(game.current_player).iter().enumerate() {
+            for orientation in game.piece_rules.orientations() {
+                choices.push((piece_index, orientation));
+                pieces.push(piece.oriented(orientation));
+            }
+        }
+        best_of_pieces(self, game, &pieces).map(|(index, (x, y))| {
+            let (piece_index, orientation) = choices[index];
+            HandMove { piece_index, orientation, x, y }
+        })
+    }
+
+    /// Chooses a hand move like `choose_hand_move` and reports how it was
+    /// chosen, like `analyze`.
+    fn analyze_hand(&mut self, game: &GameState) -> SearchAnalysis {
+        let start_time = Instant::now();
+        let best_hand_move = self.choose_hand_move(game);
+        let best_move = best_hand_move.map(|hand_move| (hand_move.x, hand_move.y));
+
+        SearchAnalysis {
+            best_move,
+            best_hand_move,
+            principal_variation: best_move.into_iter().collect(),
+            depth: 1,
+            elapsed: start_time.elapsed(),
+            ..SearchAnalysis::default()
+        }
+    }
+
+    /// Chooses an orientation of `piece` allowed by the rules and where to
+    /// place it, comparing orientations like `choose_hand_move` compares
+    /// pieces.
+    fn choose_oriented_move(&mut self, game: &GameState, piece: &Piece) -> Option<OrientedMove> {
+        let orientations = game.piece_rules.orientations();
+        let pieces: Vec<Piece> = orientations.iter().map(|&orientation| piece.oriented(orientation)).collect();
+        best_of_pieces(self, game, &pieces)
+            .map(|(index, (x, y))| OrientedMove { orientation: orientations[index], x, y })
+    }
+
+    /// Chooses a move and reports how it was chosen. AIs without scores
+    /// of their own only report the move and the time taken.
+    fn analyze(&mut self, game: &GameState, piece: &Piece) -> SearchAnalysis {
+        let start_time = Instant::now();
+        let best_move = self.choose_move(game, piece);
+
+        SearchAnalysis {
+            best_move,
+            principal_variation: best_move.into_iter().collect(),
+            depth: 1,
+            elapsed: start_time.elapsed(),
+            ..SearchAnalysis::default()
+        }
+    }
+}
+
+/// Analyses each distinct piece and returns the index and move of the best
+/// one. Scores from one AI are comparable across pieces; without scores the
+/// first piece that fits wins.
+fn best_of_pieces<A: AIPlayer + ?Sized>(
+    ai: &mut A,
+    game: &GameState,
+    pieces: &[Piece],
+) -> Option<(usize, (usize, usize))> {
+    let mut best: Option<(usize, (usize, usize), Option<i32>)> = None;
+
+    for (index, piece) in pieces.iter().enumerate() {
+        if pieces[..index].iter().any(|earlier| earlier.shape == piece.shape) {
+            continue;
+        }
+        let analysis = ai.analyze(game, piece);
+        let Some(best_move) = analysis.best_move else {
+            continue;
+        };
+        let score = analysis.move_scores.first().map(|&(_, score)| score);
+        let better = match (&best, score) {
+            (None, _) => true,
+            (Some((_, _, Some(best_score))), Some(score)) => score > *best_score,
+            _ => false,
+        };
+        if better {
+            best = Some((index, best_move, score));
+        }
+    }
+
+    best.map(|(index, best_move, _)| (index, best_move))
 }
 
 pub struct RandomAI {
     name: String,
+    rng: StdRng,
 }
 
 impl RandomAI {
+    /// A random AI seeded from entropy; use `with_seed` to reproduce games.
     pub fn new() -> Self {
+        Self::with_seed(random_seed())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
         Self {
             name: "Random AI".to_string(),
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 }
 
+impl Default for RandomAI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AIPlayer for RandomAI {
     fn choose_move(&mut self, game: &GameState, piece: &Piece) -> Option<(usize, usize)> {
         let valid_moves = game.get_valid_moves(piece);
@@ -33,12 +248,26 @@ impl AIPlayer for RandomAI {
             return None;
         }
         
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..valid_moves.len());
+        let index = self.rng.gen_range(0..valid_moves.len());
         Some(valid_moves[index])
     }
 
+    fn choose_hand_move(&mut self, game: &GameState) -> Option<HandMove> {
+        let hand_moves = game.get_hand_moves();
+        if hand_moves.is_empty() {
+            return None;
+        }
+        Some(hand_moves[self.rng.gen_range(0..hand_moves.len())])
+    }
+
+    fn choose_oriented_move(&mut self, game: &GameState, piece: &Piece) -> Option<OrientedMove> {
+        let oriented_moves = game.get_oriented_moves(piece);
+        if oriented_moves.is_empty() {
+            return None;
+        }
+        Some(oriented_moves[self.rng.gen_range(0..oriented_moves.len())])
+    }
+
     fn get_name(&self) -> &str {
         &self.name
     }
@@ -46,12 +275,18 @@ impl AIPlayer for RandomAI {
 
 pub struct GreedyAI {
     name: String,
+    weights: EvalWeights,
 }
 
 impl GreedyAI {
     pub fn new() -> Self {
+        Self::with_weights(EvalWeights::default())
+    }
+
+    pub fn with_weights(weights: EvalWeights) -> Self {
         Self {
             name: "Greedy AI".to_string(),
+            weights,
         }
     }
 
@@ -59,44 +294,31 @@ impl GreedyAI {
         let mut score = 0;
         
         // Score based on piece size (larger pieces are better)
-        score += piece.shape.len() as i32 * 10;
+        score += piece.shape.len() as i32 * self.weights.piece_size;
         
         // Score based on proximity to center
         let center_x = game.width / 2;
         let center_y = game.height / 2;
-        let distance_to_center = ((x as i32 - center_x as i32).abs() + (y as i32 - center_y as i32).abs()) as i32;
-        score -= distance_to_center;
+        let distance_to_center = (x as i32 - center_x as i32).abs() + (y as i32 - center_y as i32).abs();
+        score -= distance_to_center * self.weights.center_distance;
         
         // Score based on blocking opponent
         let opponent_territory_nearby = self.count_opponent_cells_nearby(game, piece, x, y);
-        score += opponent_territory_nearby * 5;
+        score += opponent_territory_nearby * self.weights.blocking;
         
         score
     }
 
     fn count_opponent_cells_nearby(&self, game: &GameState, piece: &Piece, x: usize, y: usize) -> i32 {
         let mut count = 0;
-        let opponent_cells = if game.current_player == 1 {
-            [crate::Cell::Player2Old, crate::Cell::Player2New]
-        } else {
-            [crate::Cell::Player1Old, crate::Cell::Player1New]
-        };
 
-        for (px, py) in &piece.shape {
-            let abs_x = x + px;
-            let abs_y = y + py;
-            
+        for (abs_x, abs_y) in game.piece_cells(piece, x, y).unwrap_or_default() {
             // Check surrounding cells
             for dy in -1..=1i32 {
                 for dx in -1..=1i32 {
-                    let check_x = abs_x as i32 + dx;
-                    let check_y = abs_y as i32 + dy;
-                    
-                    if check_x >= 0 && check_y >= 0 && 
-                       (check_x as usize) < game.width && 
-                       (check_y as usize) < game.height {
-                        let cell = &game.board[check_y as usize][check_x as usize];
-                        if opponent_cells.contains(cell) {
+                    if let Some((check_x, check_y)) = game.cell_offset(abs_x, abs_y, dx, dy) {
+                        let owner = game.board[check_y][check_x].owner();
+                        if owner.is_some_and(|owner| !game.are_teammates(owner, game.current_player)) {
                             count += 1;
                         }
                     }
@@ -108,6 +330,12 @@ impl GreedyAI {
     }
 }
 
+impl Default for GreedyAI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AIPlayer for GreedyAI {
     fn choose_move(&mut self, game: &GameState, piece: &Piece) -> Option<(usize, usize)> {
         let valid_moves = game.get_valid_moves(piece);
@@ -132,66 +360,320 @@ impl AIPlayer for GreedyAI {
     fn get_name(&self) -> &str {
         &self.name
     }
+
+    fn analyze(&mut self, game: &GameState, piece: &Piece) -> SearchAnalysis {
+        let start_time = Instant::now();
+        let mut move_scores: Vec<((usize, usize), i32)> = game
+            .get_valid_moves(piece)
+            .into_iter()
+            .map(|(x, y)| ((x, y), self.evaluate_move(game, piece, x, y)))
+            .collect();
+        // Stable sort keeps the first of equal moves on top, as choose_move does
+        move_scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+        let best_move = move_scores.first().map(|(mv, _)| *mv);
+        SearchAnalysis {
+            best_move,
+            nodes: move_scores.len() as u64,
+            move_scores,
+            principal_variation: best_move.into_iter().collect(),
+            depth: 1,
+            elapsed: start_time.elapsed(),
+            ..SearchAnalysis::default()
+        }
+    }
+}
+
+/// Largest transposition table kept between moves before it is cleared.
+const MAX_TABLE_ENTRIES: usize = 1 << 18;
+/// Nodes searched between looks at the clock.
+const CLOCK_CHECK_NODES: u64 = 256;
+
+/// How a stored score relates to the true value of the position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone)]
+struct TableEntry {
+    score: i32,
+    depth: u32,
+    bound: Bound,
+    line: Vec<(usize, usize)>,
+}
+
+type TranspositionTable = Arc<Mutex<HashMap<String, TableEntry>>>;
+
+/// The time budget of one search, shared by its workers. Once the deadline
+/// passes every worker abandons the move it is searching.
+struct SearchClock {
+    deadline: Option<Instant>,
+    expired: AtomicBool,
+}
+
+impl SearchClock {
+    fn new(limit: Option<Duration>) -> Self {
+        Self {
+            deadline: limit.map(|limit| Instant::now() + limit),
+            expired: AtomicBool::new(false),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    /// Reads the clock every `CLOCK_CHECK_NODES` nodes and reports whether
+    /// the budget is spent.
+    fn check(&self, nodes: u64) -> bool {
+        if nodes % CLOCK_CHECK_NODES == 0 && self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.expired.store(true, Ordering::Relaxed);
+        }
+        self.is_expired()
+    }
+}
+
+struct PonderJob {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
 }
 
 pub struct MinimaxAI {
     name: String,
     depth: u32,
-    transposition_table: HashMap<String, (i32, u32)>,
+    transposition_table: TranspositionTable,
+    weights: EvalWeights,
+    threads: usize,
+    time_limit: Option<Duration>,
+    pondering: bool,
+    ponder_pieces: Vec<Piece>,
+    ponder_replies: usize,
+    ponder_job: Option<PonderJob>,
+    /// Set on background searches; they abandon the search when raised.
+    stop: Option<Arc<AtomicBool>>,
+    model_opponent: bool,
+    opponent_model: Option<OpponentModel>,
+    opponent_replies: Option<usize>,
 }
 
 impl MinimaxAI {
     pub fn new(depth: u32) -> Self {
+        Self::with_weights(depth, EvalWeights::default())
+    }
+
+    pub fn with_weights(depth: u32, weights: EvalWeights) -> Self {
         Self {
             name: format!("Minimax AI (depth {})", depth),
             depth,
-            transposition_table: HashMap::new(),
+            transposition_table: Arc::new(Mutex::new(HashMap::new())),
+            weights,
+            threads: 1,
+            time_limit: None,
+            pondering: false,
+            ponder_pieces: Vec::new(),
+            ponder_replies: 3,
+            ponder_job: None,
+            stop: None,
+            model_opponent: false,
+            opponent_model: None,
+            opponent_replies: None,
         }
     }
 
-    fn evaluate_position(&self, game: &GameState) -> i32 {
-        let (p1_score, p2_score) = game.calculate_scores();
-        
-        let score_diff = if game.current_player == 1 {
-            p1_score as i32 - p2_score as i32
-        } else {
-            p2_score as i32 - p1_score as i32
-        };
+    /// Splits the root moves across `threads` worker threads.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Abandons the search once `limit` has elapsed and plays the best root
+    /// move whose search finished. If none did, root moves are ranked by
+    /// the evaluation of the position they lead to.
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Searches on the opponent's time after each move. The background
+    /// search expands the opponent's most promising replies and fills the
+    /// transposition table for our next turn.
+    pub fn with_pondering(mut self, enabled: bool) -> Self {
+        self.pondering = enabled;
+        self
+    }
+
+    /// Pieces likely to be dealt, e.g. from `PieceSet::likely_pieces`.
+    /// Pondering expects the opponent to be dealt one of them and then us.
+    /// Without them the single cell and the two dominoes are assumed, since
+    /// every preset deals those.
+    pub fn with_ponder_pieces(mut self, pieces: Vec<Piece>) -> Self {
+        self.ponder_pieces = pieces;
+        self
+    }
+
+    /// Number of opponent replies expanded while pondering.
+    pub fn with_ponder_replies(mut self, replies: usize) -> Self {
+        self.ponder_replies = replies;
+        self
+    }
+
+    /// Learns the opponent's preferences from its observed moves and
+    /// searches its most likely replies first.
+    pub fn with_opponent_model(mut self, enabled: bool) -> Self {
+        self.model_opponent = enabled;
+        self
+    }
+
+    /// Once the opponent model is confident, only its `replies` most likely
+    /// replies are searched at opponent nodes.
+    pub fn with_opponent_replies(mut self, replies: usize) -> Self {
+        self.opponent_replies = Some(replies.max(1));
+        self
+    }
+
+    pub fn opponent_model(&self) -> Option<&OpponentModel> {
+        self.opponent_model.as_ref()
+    }
+
+    /// True while a background search is running.
+    pub fn is_pondering(&self) -> bool {
+        self.ponder_job.as_ref().is_some_and(|job| !job.handle.is_finished())
+    }
+
+    pub fn table_size(&self) -> usize {
+        self.transposition_table.lock().unwrap().len()
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    fn prepare_table(&self) {
+        let mut table = self.transposition_table.lock().unwrap();
+        if table.len() > MAX_TABLE_ENTRIES {
+            table.clear();
+        }
+    }
+
+    fn table_key(game: &GameState, piece: &Piece, root_player: u8) -> String {
+        let mut key = String::with_capacity(game.width * game.height + 16);
+        for row in &game.board {
+            key.extend(row.iter().map(|cell| cell.to_char()));
+        }
+        key.push_str(&format!("|{}{}|", game.current_player, root_player));
+        key.extend(game.eliminated.iter().map(|&out| if out { 'x' } else { '-' }));
+        if game.topology == Topology::Torus {
+            key.push('~');
+        }
+        for (x, y) in &piece.shape {
+            key.push_str(&format!("{},{};", x, y));
+        }
+        for upcoming in &game.upcoming {
+            key.push('/');
+            for (x, y) in &upcoming.shape {
+                key.push_str(&format!("{},{};", x, y));
+            }
+        }
+        for hand in &game.hands {
+            key.push('|');
+            for held in hand {
+                key.push('/');
+                for (x, y) in &held.shape {
+                    key.push_str(&format!("{},{};", x, y));
+                }
+            }
+        }
+        key
+    }
+
+    /// A single-threaded copy sharing this AI's table, for background use.
+    fn background_searcher(&self, stop: Arc<AtomicBool>) -> MinimaxAI {
+        MinimaxAI {
+            name: self.name.clone(),
+            depth: self.depth,
+            transposition_table: Arc::clone(&self.transposition_table),
+            weights: self.weights.clone(),
+            threads: 1,
+            time_limit: None,
+            pondering: false,
+            ponder_pieces: Vec::new(),
+            ponder_replies: 0,
+            ponder_job: None,
+            stop: Some(stop),
+            model_opponent: false,
+            opponent_model: self.opponent_model.clone(),
+            opponent_replies: self.opponent_replies,
+        }
+    }
+
+    /// Runs on the pondering thread. For each piece the opponent may be
+    /// dealt, likeliest first, its replies are expanded in the order it
+    /// would rank them with our evaluation, and each position after a reply
+    /// is searched for every piece we may be dealt.
+    fn ponder(&self, game: &GameState, pieces: &[Piece], replies: usize) {
+        let opponent = game.current_player;
+        for opponent_piece in pieces {
+            let mut ranked: Vec<((usize, usize), i32)> = game
+                .get_valid_moves(opponent_piece)
+                .into_iter()
+                .map(|(x, y)| {
+                    let mut after = game.clone();
+                    after.place_piece(opponent_piece, x, y);
+                    ((x, y), self.evaluate_position(&after, opponent))
+                })
+                .collect();
+            ranked.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+            for ((x, y), _) in ranked.into_iter().take(replies) {
+                let mut after_reply = game.clone();
+                after_reply.place_piece(opponent_piece, x, y);
+                after_reply.switch_player();
+
+                for piece in pieces {
+                    if self.stopped() {
+                        return;
+                    }
+                    let valid_moves = after_reply.get_valid_moves(piece);
+                    if valid_moves.len() > 1 {
+                        self.search_root(&after_reply, piece, &Self::placements(&valid_moves), false);
+                    }
+                }
+            }
+        }
+    }
+
+    fn evaluate_position(&self, game: &GameState, player: u8) -> i32 {
+        // Teammates' territory counts as ours; against several opposing
+        // sides, measure ourselves against the leader
+        let score_diff = game.side_score(player) as i32 - game.best_opponent_score(player) as i32;
         
         // Add positional bonuses
         let mut position_bonus = 0;
         
         // Control of center is valuable
-        let center_control = self.evaluate_center_control(game);
-        position_bonus += center_control;
+        let center_control = self.evaluate_center_control(game, player);
+        position_bonus += center_control * self.weights.center_control;
         
         // Territory compactness (connected regions are better)
-        let compactness = self.evaluate_compactness(game);
-        position_bonus += compactness;
+        let compactness = self.evaluate_compactness(game, player);
+        position_bonus += compactness * self.weights.compactness;
         
-        score_diff * 100 + position_bonus
+        score_diff * self.weights.score_diff + position_bonus
     }
 
-    fn evaluate_center_control(&self, game: &GameState) -> i32 {
+    fn evaluate_center_control(&self, game: &GameState, player: u8) -> i32 {
         let center_x = game.width / 2;
         let center_y = game.height / 2;
         let mut control = 0;
         
-        let player_cells = if game.current_player == 1 {
-            [crate::Cell::Player1Old, crate::Cell::Player1New]
-        } else {
-            [crate::Cell::Player2Old, crate::Cell::Player2New]
-        };
-        
         // Check 3x3 area around center
         for dy in -1..=1i32 {
             for dx in -1..=1i32 {
-                let x = center_x as i32 + dx;
-                let y = center_y as i32 + dy;
-                
-                if x >= 0 && y >= 0 && (x as usize) < game.width && (y as usize) < game.height {
-                    let cell = &game.board[y as usize][x as usize];
-                    if player_cells.contains(cell) {
+                if let Some((x, y)) = game.cell_offset(center_x, center_y, dx, dy) {
+                    if Self::is_friendly(game, x, y, player) {
                         control += 3 - dx.abs() - dy.abs(); // Closer to center = more points
                     }
                 }
@@ -201,20 +683,14 @@ impl MinimaxAI {
         control
     }
 
-    fn evaluate_compactness(&self, game: &GameState) -> i32 {
-        let player_cells = if game.current_player == 1 {
-            [crate::Cell::Player1Old, crate::Cell::Player1New]
-        } else {
-            [crate::Cell::Player2Old, crate::Cell::Player2New]
-        };
-        
+    fn evaluate_compactness(&self, game: &GameState, player: u8) -> i32 {
         let mut compactness = 0;
         
         for y in 0..game.height {
             for x in 0..game.width {
-                if player_cells.contains(&game.board[y][x]) {
+                if Self::is_friendly(game, x, y, player) {
                     // Count adjacent friendly cells
-                    let adjacent = self.count_adjacent_friendly(game, x, y, &player_cells);
+                    let adjacent = self.count_adjacent_friendly(game, x, y, player);
                     compactness += adjacent;
                 }
             }
@@ -223,21 +699,15 @@ impl MinimaxAI {
         compactness
     }
 
-    fn count_adjacent_friendly(&self, game: &GameState, x: usize, y: usize, friendly_cells: &[crate::Cell; 2]) -> i32 {
+    fn count_adjacent_friendly(&self, game: &GameState, x: usize, y: usize, player: u8) -> i32 {
         let mut count = 0;
         
         for dy in -1..=1i32 {
             for dx in -1..=1i32 {
                 if dx == 0 && dy == 0 { continue; }
                 
-                let check_x = x as i32 + dx;
-                let check_y = y as i32 + dy;
-                
-                if check_x >= 0 && check_y >= 0 && 
-                   (check_x as usize) < game.width && 
-                   (check_y as usize) < game.height {
-                    let cell = &game.board[check_y as usize][check_x as usize];
-                    if friendly_cells.contains(cell) {
+                if let Some((check_x, check_y)) = game.cell_offset(x, y, dx, dy) {
+                    if Self::is_friendly(game, check_x, check_y, player) {
                         count += 1;
                     }
                 }
@@ -247,52 +717,320 @@ impl MinimaxAI {
         count
     }
 
-    fn minimax(&mut self, game: &mut GameState, piece: &Piece, depth: u32, alpha: i32, beta: i32, maximizing: bool) -> i32 {
+    /// Whether the cell belongs to `player` or a teammate.
+    fn is_friendly(game: &GameState, x: usize, y: usize, player: u8) -> bool {
+        game.board[y][x].owner().is_some_and(|owner| game.are_teammates(owner, player))
+    }
+
+    /// Alpha-beta search scored from `root_player`'s point of view: nodes
+    /// where `root_player` or a teammate is to move maximize, all others
+    /// minimize. With more than two sides this is a paranoid search: every
+    /// opponent is assumed to play against `root_player`. The best line found below this
+    /// node is written to `line`.
+    #[allow(clippy::too_many_arguments)]
+    fn minimax(
+        &self,
+        game: &GameState,
+        piece: &Piece,
+        depth: u32,
+        mut alpha: i32,
+        mut beta: i32,
+        root_player: u8,
+        line: &mut Vec<(usize, usize)>,
+        nodes: &mut u64,
+        clock: &SearchClock,
+    ) -> i32 {
+        *nodes += 1;
+        line.clear();
+
+        if self.stopped() || clock.check(*nodes) {
+            return 0;
+        }
+
         if depth == 0 || game.game_over {
-            return self.evaluate_position(game);
+            return self.evaluate_position(game, root_player);
         }
         
-        let valid_moves = game.get_valid_moves(piece);
-        if valid_moves.is_empty() {
-            return self.evaluate_position(game);
+        // Entries are only reused at the same depth so that results match a
+        // search without the table
+        let key = Self::table_key(game, piece, root_player);
+        if let Some(entry) = self.transposition_table.lock().unwrap().get(&key) {
+            if entry.depth == depth {
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if usable {
+                    line.extend_from_slice(&entry.line);
+                    return entry.score;
+                }
+            }
         }
         
-        if maximizing {
-            let mut max_eval = i32::MIN;
-            for &(x, y) in &valid_moves {
-                let mut game_copy = game.clone();
-                game_copy.place_piece(piece, x, y);
-                game_copy.switch_player();
-                
-                let eval = self.minimax(&mut game_copy, piece, depth - 1, alpha, beta, false);
-                max_eval = max_eval.max(eval);
-                
-                if beta <= alpha {
-                    break; // Alpha-beta pruning
-                }
+        let maximizing = game.are_teammates(game.current_player, root_player);
+        let moves = self.search_moves(game, piece, maximizing);
+        if moves.is_empty() {
+            return self.evaluate_position(game, root_player);
+        }
+        
+        let (alpha_start, beta_start) = (alpha, beta);
+        let mut best_eval = if maximizing { i32::MIN } else { i32::MAX };
+        let mut child_line = Vec::new();
+        // Previewed pieces are known; past the preview, assume the same piece
+        let next_piece = game.next_piece(piece);
+        
+        for &search_move in &moves {
+            let game_copy = Self::play(game, piece, search_move);
+            let (x, y) = search_move.position();
+            
+            let eval = self.minimax(&game_copy, next_piece, depth - 1, alpha, beta, root_player, &mut child_line, nodes, clock);
+            if self.stopped() || clock.is_expired() {
+                return 0;
+            }
+            
+            let improved = if maximizing { eval > best_eval } else { eval < best_eval };
+            if improved || line.is_empty() {
+                best_eval = eval;
+                line.clear();
+                line.push((x, y));
+                line.extend_from_slice(&child_line);
             }
-            max_eval
+            
+            if maximizing {
+                alpha = alpha.max(eval);
+            } else {
+                beta = beta.min(eval);
+            }
+            
+            if beta <= alpha {
+                break; // Alpha-beta pruning
+            }
+        }
+        
+        let bound = if best_eval <= alpha_start {
+            Bound::Upper
+        } else if best_eval >= beta_start {
+            Bound::Lower
         } else {
-            let mut min_eval = i32::MAX;
-            for &(x, y) in &valid_moves {
-                let mut game_copy = game.clone();
-                game_copy.place_piece(piece, x, y);
-                game_copy.switch_player();
-                
-                let eval = self.minimax(&mut game_copy, piece, depth - 1, alpha, beta, true);
-                min_eval = min_eval.min(eval);
-                
-                if beta <= alpha {
-                    break; // Alpha-beta pruning
+            Bound::Exact
+        };
+        self.transposition_table.lock().unwrap().insert(key, TableEntry {
+            score: best_eval,
+            depth,
+            bound,
+            line: line.clone(),
+        });
+        
+        best_eval
+    }
+
+    /// Moves to search at a node. In the hand variant these are the mover's
+    /// hand moves; otherwise placements of `piece`, with the opponent's
+    /// likely replies first when it is modelled.
+    fn search_moves(&self, game: &GameState, piece: &Piece, maximizing: bool) -> Vec<SearchMove> {
+        if game.uses_hands() {
+            return game.get_hand_moves().into_iter().map(SearchMove::Hand).collect();
+        }
+        if !game.piece_rules.is_fixed() {
+            return game.get_oriented_moves(piece).into_iter().map(SearchMove::Oriented).collect();
+        }
+
+        let mut valid_moves = game.get_valid_moves(piece);
+        // Likely replies first, so the model's predictions prune the most
+        if let Some(model) = &self.opponent_model {
+            if !maximizing && model.player == game.current_player && model.is_confident() {
+                model.rank_moves(game, piece, &mut valid_moves);
+                if let Some(replies) = self.opponent_replies {
+                    valid_moves.truncate(replies);
                 }
             }
-            min_eval
+        }
+        valid_moves.into_iter().map(|(x, y)| SearchMove::Place(x, y)).collect()
+    }
+
+    fn placements(valid_moves: &[(usize, usize)]) -> Vec<SearchMove> {
+        valid_moves.iter().map(|&(x, y)| SearchMove::Place(x, y)).collect()
+    }
+
+    /// The position after `search_move`, with the turn passed on. A hand
+    /// piece is not replaced, since the refill is not known yet.
+    fn play(game: &GameState, piece: &Piece, search_move: SearchMove) -> GameState {
+        let mut game_copy = game.clone();
+        match search_move {
+            SearchMove::Place(x, y) => {
+                game_copy.place_piece(piece, x, y);
+            }
+            SearchMove::Hand(hand_move) => {
+                game_copy.play_hand_move(hand_move);
+            }
+            SearchMove::Oriented(oriented_move) => {
+                game_copy.place_oriented(piece, oriented_move);
+            }
+        }
+        game_copy.switch_player();
+        game_copy.pop_preview();
+        game_copy
+    }
+
+    /// Searches the root moves on `self.threads` workers. Workers pull moves
+    /// from a shared index. With `exact` unset they read the best score
+    /// found so far as their alpha bound; the bound is lowered by one so
+    /// that ties are scored exactly and resolved by move order, keeping the
+    /// choice identical to a single-threaded search. With `exact` set every
+    /// move gets a full window so all scores are exact. Moves still being
+    /// searched when the time limit runs out are left out of the results.
+    fn search_root(&self, game: &GameState, piece: &Piece, valid_moves: &[SearchMove], exact: bool) -> RootSearch {
+        let start_time = Instant::now();
+        let clock = SearchClock::new(self.time_limit);
+        let root_player = game.current_player;
+        let next_move = AtomicUsize::new(0);
+        let best_score = AtomicI32::new(i32::MIN);
+        let total_nodes = AtomicU64::new(1);
+        let results = Mutex::new(Vec::with_capacity(valid_moves.len()));
+        let workers = self.threads.min(valid_moves.len());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    let mut nodes = 0;
+                    loop {
+                        let index = next_move.fetch_add(1, Ordering::SeqCst);
+                        if index >= valid_moves.len() || clock.is_expired() {
+                            break;
+                        }
+
+                        let game_copy = Self::play(game, piece, valid_moves[index]);
+
+                        let alpha = if exact {
+                            i32::MIN
+                        } else {
+                            best_score.load(Ordering::SeqCst).saturating_sub(1)
+                        };
+                        let mut line = Vec::new();
+                        let score = self.minimax(
+                            &game_copy,
+                            game.next_piece(piece),
+                            self.depth.saturating_sub(1),
+                            alpha,
+                            i32::MAX,
+                            root_player,
+                            &mut line,
+                            &mut nodes,
+                            &clock,
+                        );
+                        if clock.is_expired() {
+                            break;
+                        }
+                        line.insert(0, valid_moves[index].position());
+
+                        best_score.fetch_max(score, Ordering::SeqCst);
+                        results.lock().unwrap().push(RootResult { index, score, line });
+                    }
+                    total_nodes.fetch_add(nodes, Ordering::SeqCst);
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        let shallow = results.is_empty() && clock.is_expired() && !self.stopped();
+        if shallow {
+            // Out of time before any move finished: fall back to one ply
+            results = valid_moves
+                .iter()
+                .enumerate()
+                .map(|(index, &search_move)| RootResult {
+                    index,
+                    score: self.evaluate_position(&Self::play(game, piece, search_move), root_player),
+                    line: vec![search_move.position()],
+                })
+                .collect();
+        }
+        // Highest score first; ties go to the earliest move
+        results.sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+
+        RootSearch {
+            results,
+            shallow,
+            nodes: total_nodes.into_inner(),
+            elapsed: start_time.elapsed(),
+        }
+    }
+
+    /// Searches every root move with exact scores and reports the result.
+    fn analyze_moves(&self, game: &GameState, piece: &Piece, moves: &[SearchMove]) -> SearchAnalysis {
+        let search = self.search_root(game, piece, moves, true);
+        let best = search.results.first();
+        let mut searched = vec![false; moves.len()];
+        for result in &search.results {
+            searched[result.index] = true;
+        }
+        let hand_move = |index: usize| match moves[index] {
+            SearchMove::Hand(hand_move) => Some(hand_move),
+            _ => None,
+        };
+        
+        SearchAnalysis {
+            best_move: best.map(|result| moves[result.index].position()),
+            move_scores: search
+                .results
+                .iter()
+                .map(|result| (moves[result.index].position(), result.score))
+                .collect(),
+            unsearched: moves
+                .iter()
+                .zip(&searched)
+                .filter(|(_, &done)| !done)
+                .map(|(search_move, _)| search_move.position())
+                .collect(),
+            principal_variation: best.map(|result| result.line.clone()).unwrap_or_default(),
+            best_hand_move: best.and_then(|result| hand_move(result.index)),
+            hand_moves: search.results.iter().filter_map(|result| hand_move(result.index)).collect(),
+            nodes: search.nodes,
+            // Out of time before any move finished, every move was scored one ply deep
+            depth: if search.shallow { 1 } else { self.depth },
+            elapsed: search.elapsed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SearchMove {
+    Place(usize, usize),
+    Hand(HandMove),
+    Oriented(OrientedMove),
+}
+
+impl SearchMove {
+    fn position(&self) -> (usize, usize) {
+        match *self {
+            SearchMove::Place(x, y) => (x, y),
+            SearchMove::Hand(hand_move) => (hand_move.x, hand_move.y),
+            SearchMove::Oriented(oriented_move) => (oriented_move.x, oriented_move.y),
         }
     }
 }
 
+struct RootResult {
+    index: usize,
+    score: i32,
+    line: Vec<(usize, usize)>,
+}
+
+struct RootSearch {
+    /// Searched root moves, best first.
+    results: Vec<RootResult>,
+    /// The time ran out before any move finished, so every move was scored
+    /// one ply deep instead.
+    shallow: bool,
+    nodes: u64,
+    elapsed: Duration,
+}
+
 impl AIPlayer for MinimaxAI {
     fn choose_move(&mut self, game: &GameState, piece: &Piece) -> Option<(usize, usize)> {
+        self.stop_pondering();
+        
         let valid_moves = game.get_valid_moves(piece);
         if valid_moves.is_empty() {
             return None;
@@ -302,35 +1040,218 @@ impl AIPlayer for MinimaxAI {
             return Some(valid_moves[0]);
         }
         
-        let mut best_move = valid_moves[0];
-        let mut best_score = i32::MIN;
+        self.prepare_table();
         
-        for &(x, y) in &valid_moves {
-            let mut game_copy = game.clone();
-            game_copy.place_piece(piece, x, y);
-            game_copy.switch_player();
-            
-            let score = self.minimax(&mut game_copy, piece, self.depth - 1, i32::MIN, i32::MAX, false);
-            
-            if score > best_score {
-                best_score = score;
-                best_move = (x, y);
-            }
+        let search = self.search_root(game, piece, &Self::placements(&valid_moves), false);
+        search.results.first().map(|best| valid_moves[best.index])
+    }
+
+    fn choose_hand_move(&mut self, game: &GameState) -> Option<HandMove> {
+        self.stop_pondering();
+        
+        let hand_moves = game.get_hand_moves();
+        if hand_moves.len() <= 1 {
+            return hand_moves.first().copied();
         }
         
-        Some(best_move)
+        self.prepare_table();
+        
+        let moves: Vec<SearchMove> = hand_moves.iter().copied().map(SearchMove::Hand).collect();
+        // Only hand pieces are played, so any piece serves as the placeholder
+        let placeholder = &game.hand(game.current_player)[0];
+        let search = self.search_root(game, placeholder, &moves, false);
+        search.results.first().map(|best| hand_moves[best.index])
+    }
+
+    fn choose_oriented_move(&mut self, game: &GameState, piece: &Piece) -> Option<OrientedMove> {
+        self.stop_pondering();
+        
+        let oriented_moves = game.get_oriented_moves(piece);
+        if oriented_moves.len() <= 1 {
+            return oriented_moves.first().copied();
+        }
+        
+        self.prepare_table();
+        
+        let moves: Vec<SearchMove> = oriented_moves.iter().copied().map(SearchMove::Oriented).collect();
+        let search = self.search_root(game, piece, &moves, false);
+        search.results.first().map(|best| oriented_moves[best.index])
     }
 
     fn get_name(&self) -> &str {
         &self.name
     }
+
+    fn analyze(&mut self, game: &GameState, piece: &Piece) -> SearchAnalysis {
+        self.stop_pondering();
+        
+        let valid_moves = game.get_valid_moves(piece);
+        if valid_moves.is_empty() {
+            return SearchAnalysis {
+                depth: self.depth,
+                ..SearchAnalysis::default()
+            };
+        }
+        
+        self.prepare_table();
+        
+        self.analyze_moves(game, piece, &Self::placements(&valid_moves))
+    }
+
+    fn analyze_hand(&mut self, game: &GameState) -> SearchAnalysis {
+        self.stop_pondering();
+        
+        let hand_moves = game.get_hand_moves();
+        if hand_moves.is_empty() {
+            return SearchAnalysis {
+                depth: self.depth,
+                ..SearchAnalysis::default()
+            };
+        }
+        
+        self.prepare_table();
+        
+        let moves: Vec<SearchMove> = hand_moves.iter().copied().map(SearchMove::Hand).collect();
+        let placeholder = &game.hand(game.current_player)[0];
+        self.analyze_moves(game, placeholder, &moves)
+    }
+
+    fn start_pondering(&mut self, game: &GameState, _piece: &Piece) {
+        self.stop_pondering();
+        // With a preview queue or hands, positions are keyed by pieces we
+        // cannot know yet (the preview's new last piece), so pondered
+        // entries would never be hit. Pondering only considers fixed pieces.
+        if !self.pondering
+            || game.game_over
+            || !game.upcoming.is_empty()
+            || game.uses_hands()
+            || !game.piece_rules.is_fixed()
+        {
+            return;
+        }
+        
+        let stop = Arc::new(AtomicBool::new(false));
+        let searcher = self.background_searcher(Arc::clone(&stop));
+        let game = game.clone();
+        let pieces = if self.ponder_pieces.is_empty() {
+            Piece::fixed_polyominoes(1).into_iter().chain(Piece::fixed_polyominoes(2)).collect()
+        } else {
+            self.ponder_pieces.clone()
+        };
+        let replies = self.ponder_replies;
+        
+        let handle = thread::spawn(move || {
+            searcher.prepare_table();
+            searcher.ponder(&game, &pieces, replies);
+        });
+        self.ponder_job = Some(PonderJob { stop, handle });
+    }
+
+    fn stop_pondering(&mut self) {
+        if let Some(job) = self.ponder_job.take() {
+            job.stop.store(true, Ordering::Relaxed);
+            job.handle.join().ok();
+        }
+    }
+
+    fn observe_opponent_move(&mut self, before: &GameState, after: &GameState, piece: Option<&Piece>) {
+        if !self.model_opponent {
+            return;
+        }
+        
+        self.stop_pondering();
+        let model = self
+            .opponent_model
+            .get_or_insert_with(|| OpponentModel::new(before.current_player));
+        model.observe(before, after, piece);
+        
+        // Pruned scores depend on the model, so older entries are stale
+        if self.opponent_replies.is_some() {
+            self.transposition_table.lock().unwrap().clear();
+        }
+    }
+
+    fn opponent_style(&self) -> Option<OpponentStyle> {
+        self.opponent_model.as_ref().map(|model| model.style())
+    }
+}
+
+impl Drop for MinimaxAI {
+    fn drop(&mut self) {
+        self.stop_pondering();
+    }
+}
+
+/// Settings shared by every AI built through `create_ai_with_options`.
+#[derive(Debug, Clone)]
+pub struct AIOptions {
+    pub weights: EvalWeights,
+    /// Worker threads for the Minimax root search.
+    pub threads: usize,
+    /// Per-move search budget for Minimax.
+    pub time_limit: Option<Duration>,
+    /// Lets Minimax search on the opponent's time.
+    pub ponder: bool,
+    /// Pieces Minimax prepares for while pondering; see
+    /// `MinimaxAI::with_ponder_pieces`.
+    pub ponder_pieces: Vec<Piece>,
+    /// Lets Minimax learn the opponent's move preferences.
+    pub model_opponent: bool,
+    /// Replies Minimax searches at opponent nodes once the model is confident.
+    pub opponent_replies: Option<usize>,
+    /// Seed for AIs that make random choices; `None` draws one from entropy.
+    pub seed: Option<u64>,
+}
+
+impl Default for AIOptions {
+    fn default() -> Self {
+        Self {
+            weights: EvalWeights::default(),
+            threads: 1,
+            time_limit: None,
+            ponder: false,
+            ponder_pieces: Vec::new(),
+            model_opponent: false,
+            opponent_replies: None,
+            seed: None,
+        }
+    }
+}
+
+/// Number of threads the machine can run in parallel, or 1 if unknown.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
 }
 
 pub fn create_ai(difficulty: AIDifficulty) -> Box<dyn AIPlayer> {
+    create_ai_with_options(difficulty, &AIOptions::default())
+}
+
+pub fn create_ai_with_weights(difficulty: AIDifficulty, weights: EvalWeights) -> Box<dyn AIPlayer> {
+    create_ai_with_options(difficulty, &AIOptions { weights, ..AIOptions::default() })
+}
+
+pub fn create_ai_with_options(difficulty: AIDifficulty, options: &AIOptions) -> Box<dyn AIPlayer> {
+    let minimax = |depth: u32| {
+        let ai = MinimaxAI::with_weights(depth, options.weights.clone())
+            .with_threads(options.threads)
+            .with_pondering(options.ponder)
+            .with_ponder_pieces(options.ponder_pieces.clone())
+            .with_opponent_model(options.model_opponent);
+        let ai = match options.opponent_replies {
+            Some(replies) => ai.with_opponent_replies(replies),
+            None => ai,
+        };
+        match options.time_limit {
+            Some(limit) => ai.with_time_limit(limit),
+            None => ai,
+        }
+    };
+
     match difficulty {
-        AIDifficulty::Easy => Box::new(RandomAI::new()),
-        AIDifficulty::Medium => Box::new(GreedyAI::new()),
-        AIDifficulty::Hard => Box::new(MinimaxAI::new(3)),
-        AIDifficulty::Expert => Box::new(MinimaxAI::new(5)),
+        AIDifficulty::Easy => Box::new(RandomAI::with_seed(options.seed.unwrap_or_else(random_seed))),
+        AIDifficulty::Medium => Box::new(GreedyAI::with_weights(options.weights.clone())),
+        AIDifficulty::Hard => Box::new(minimax(3)),
+        AIDifficulty::Expert => Box::new(minimax(5)),
     }
 }
@@ -1,4 +1,4 @@
-use std::io::{self, BufRead, Write};
+use std::io::{self, Write};
 
 fn main() -> io::Result<()> {
     // Just output a move immediately
@@ -1,8 +1,12 @@
 use std::env;
+use std::fs::File;
 use std::io::{self, Write, BufRead, BufReader};
 use std::process::{Command, Stdio};
 use filler::*;
 
+/// Pieces a pondering AI prepares for on each side.
+const PONDER_PIECES: usize = 3;
+
 fn main() -> io::Result<()> {
     let args: Vec<String> = env::args().collect();
     
@@ -14,6 +18,26 @@ fn main() -> io::Result<()> {
         println!("  -p2 <player> Player 2 executable");
         println!("  -h           Human vs AI mode");
         println!("  --ai-vs-ai   AI vs AI mode");
+        println!("  --weights <file>     Load evaluation weights");
+        println!("  --weight <name=val>  Override one evaluation weight");
+        println!("  --threads <n>        Minimax search threads (default: all cores)");
+        println!("  --move-time <ms>     Minimax time budget per move");
+        println!("  --debug-log <file>   Write built-in AI analysis for every move");
+        println!("  --ponder             Let Minimax search on the opponent's time");
+        println!("  --model-opponent     Let Minimax learn the opponent's move preferences");
+        println!("  --opponent-replies <k>  Search only the opponent's k likeliest replies");
+        println!("  --piece-set <name>   Piece preset: classic, official or small");
+        println!("  --pieces <file>      Load the piece set from a file");
+        println!("  --preview <k>        Show both players the next k pieces");
+        println!("  --rotate             Allow rotating pieces before placing them");
+        println!("  --mirror             Allow mirroring pieces before placing them");
+        println!("  --hand <n>           Each player holds n pieces and picks one per move");
+        println!("  --seed <n>           Master seed for pieces and AIs (default: random)");
+        println!("  --teams <list>       Team of each player, e.g. 1,2,1,2");
+        println!("  --torus              Join opposite board edges so pieces wrap around");
+        println!("  --resume <file>      Continue a game saved with 'save <file>'");
+        println!("  --replay-dir <dir>   Where to write the game's replay (default replays)");
+        println!("  --no-replay          Do not write a replay");
         return Ok(());
     }
 
@@ -22,6 +46,23 @@ fn main() -> io::Result<()> {
     let mut player2 = None;
     let mut human_mode = false;
     let mut ai_vs_ai = false;
+    let mut weights_file = None;
+    let mut weight_overrides = Vec::new();
+    let mut threads = available_threads();
+    let mut move_time = None;
+    let mut debug_log_file = None;
+    let mut ponder = false;
+    let mut model_opponent = false;
+    let mut opponent_replies = None;
+    let mut seed = None;
+    let mut teams = None;
+    let mut topology = Topology::Bounded;
+    let mut preview = 0;
+    let mut hand_size = 0;
+    let mut piece_rules = PieceRules::default();
+    let mut piece_source = "classic".to_string();
+    let mut resume_file = None;
+    let mut replay_dir = Some("replays".to_string());
     
     let mut i = 1;
     while i < args.len() {
@@ -58,31 +99,278 @@ fn main() -> io::Result<()> {
                 ai_vs_ai = true;
                 i += 1;
             }
+            "--weights" => {
+                if i + 1 < args.len() {
+                    weights_file = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--weight" => {
+                if i + 1 < args.len() {
+                    weight_overrides.push(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--threads" => {
+                if i + 1 < args.len() {
+                    threads = args[i + 1].parse().unwrap_or(threads);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--ponder" => {
+                ponder = true;
+                i += 1;
+            }
+            "--model-opponent" => {
+                model_opponent = true;
+                i += 1;
+            }
+            "--opponent-replies" => {
+                if i + 1 < args.len() {
+                    opponent_replies = args[i + 1].parse().ok();
+                    model_opponent = true;
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--piece-set" => {
+                if i + 1 < args.len() {
+                    if PieceSet::preset(&args[i + 1]).is_none() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unknown piece set '{}', expected one of {:?}", args[i + 1], PieceSet::PRESET_NAMES),
+                        ));
+                    }
+                    piece_source = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--pieces" => {
+                if i + 1 < args.len() {
+                    PieceSet::load(&args[i + 1])?;
+                    piece_source = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--preview" => {
+                if i + 1 < args.len() {
+                    preview = args[i + 1].parse().unwrap_or(0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--rotate" => {
+                piece_rules.rotate = true;
+                i += 1;
+            }
+            "--mirror" => {
+                piece_rules.mirror = true;
+                i += 1;
+            }
+            "--hand" => {
+                if i + 1 < args.len() {
+                    hand_size = args[i + 1].parse().unwrap_or(0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--seed" => {
+                if i + 1 < args.len() {
+                    seed = args[i + 1].parse().ok();
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--torus" => {
+                topology = Topology::Torus;
+                i += 1;
+            }
+            "--no-replay" => {
+                replay_dir = None;
+                i += 1;
+            }
+            "--replay-dir" => {
+                if i + 1 < args.len() {
+                    replay_dir = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--teams" => {
+                if i + 1 < args.len() {
+                    teams = Some(parse_teams(&args[i + 1])?);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--resume" => {
+                if i + 1 < args.len() {
+                    resume_file = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--debug-log" => {
+                if i + 1 < args.len() {
+                    debug_log_file = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--move-time" => {
+                if i + 1 < args.len() {
+                    move_time = args[i + 1].parse().ok().map(std::time::Duration::from_millis);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
             _ => i += 1,
         }
     }
 
-    // Default settings
-    let board_size = if let Some(_) = map_file {
-        // Parse map file if provided
-        (15, 10) // Default for now
-    } else {
-        (15, 10)
+    let mut game = match map_file {
+        Some(path) => GameState::load_map(path)?,
+        None => GameState::new(15, 10),
+    };
+    game.piece_rules = piece_rules;
+    game.topology = topology;
+    if let Some(teams) = teams {
+        game.set_teams(teams)?;
+    }
+
+    // A resumed game brings its own board, rules, pieces and seats; seats
+    // given on the command line still take precedence
+    let mut pieces_dealt = 0;
+    if let Some(path) = resume_file {
+        let saved = SavedGame::load(&path)?;
+        println!("Resuming {} at turn {}", path, saved.game.turn);
+        for (index, player) in saved.players.iter().enumerate() {
+            match (index + 1, player.split_once(' ').map_or((player.as_str(), ""), |(kind, rest)| (kind, rest))) {
+                (1, ("human", _)) => human_mode = true,
+                (1, ("ai", _)) => ai_vs_ai = true,
+                (1, ("bot", command)) if player1.is_none() => player1 = Some(command.to_string()),
+                (2, ("bot", command)) if player2.is_none() => player2 = Some(command.to_string()),
+                _ => {}
+            }
+        }
+        game = saved.game;
+        seed = Some(saved.seed);
+        piece_source = saved.piece_set;
+        preview = saved.preview;
+        pieces_dealt = saved.pieces_dealt;
+    }
+
+    let weights_config = match weights_file {
+        Some(path) => WeightsConfig::load(path)?,
+        None => WeightsConfig::default(),
+    };
+    let mut weights = weights_config.for_board(game.width, game.height).clone();
+    for assignment in &weight_overrides {
+        weights.apply_override(assignment)?;
+    }
+
+    let ai_options = AIOptions {
+        weights,
+        threads,
+        time_limit: move_time,
+        ponder,
+        ponder_pieces: Vec::new(),
+        model_opponent,
+        opponent_replies,
+        seed: None,
     };
 
-    run_terminal_game(board_size.0, board_size.1, player1, player2, human_mode, ai_vs_ai)
+    let mut debug_log = match debug_log_file {
+        Some(path) => Some(File::create(path)?),
+        None => None,
+    };
+
+    // Recorded so that any game can be replayed with --seed
+    let seed = seed.unwrap_or_else(random_seed);
+    println!("Seed: {}", seed);
+    if let Some(log) = debug_log.as_mut() {
+        writeln!(log, "Seed: {}", seed)?;
+    }
+
+    let settings = EngineSettings {
+        player1_cmd: player1,
+        player2_cmd: player2,
+        human_mode,
+        ai_vs_ai,
+        ai_options,
+        seed,
+        piece_source,
+        pieces_dealt,
+        preview,
+        hand_size,
+        debug_log,
+        replay_dir,
+    };
+
+    run_terminal_game(game, settings)
 }
 
-fn run_terminal_game(
-    width: usize, 
-    height: usize, 
-    player1_cmd: Option<String>, 
+struct EngineSettings {
+    player1_cmd: Option<String>,
     player2_cmd: Option<String>,
     human_mode: bool,
-    ai_vs_ai: bool
-) -> io::Result<()> {
-    let mut game = GameState::new(width, height);
-    let mut piece_generator = PieceGenerator::new(42);
+    ai_vs_ai: bool,
+    ai_options: AIOptions,
+    seed: u64,
+    /// Piece-set preset name or file, as recorded in saved games.
+    piece_source: String,
+    /// Pieces already dealt in a resumed game.
+    pieces_dealt: usize,
+    preview: usize,
+    hand_size: usize,
+    debug_log: Option<File>,
+    /// Where the replay is written when the game is over.
+    replay_dir: Option<String>,
+}
+
+fn run_terminal_game(mut game: GameState, settings: EngineSettings) -> io::Result<()> {
+    let EngineSettings {
+        player1_cmd,
+        player2_cmd,
+        human_mode,
+        ai_vs_ai,
+        ai_options,
+        seed,
+        piece_source,
+        pieces_dealt,
+        preview,
+        hand_size,
+        mut debug_log,
+        replay_dir,
+    } = settings;
+    let mut piece_generator = PieceGenerator::with_set(derive_seed(seed, SeedStream::Pieces), load_piece_set(&piece_source)?);
+    piece_generator.skip(pieces_dealt);
+    // Pondering prepares for the pieces this game is most likely to deal
+    let ponder_pieces = piece_generator.piece_set().likely_pieces(PONDER_PIECES);
+    let player_options = |player: u8| AIOptions {
+        seed: Some(derive_seed(seed, SeedStream::Player(player))),
+        ponder_pieces: ponder_pieces.clone(),
+        ..ai_options.clone()
+    };
     
     // Initialize players
     let mut p1_process = if let Some(ref cmd) = player1_cmd {
@@ -97,15 +385,15 @@ fn run_terminal_game(
         None
     };
 
-    // Create AI players if needed
-    let mut ai1 = if ai_vs_ai && p1_process.is_none() {
-        Some(create_ai(AIDifficulty::Hard))
+    // Create AI players if needed; seats past the second are always built-in
+    let ai1 = if ai_vs_ai && p1_process.is_none() {
+        Some(create_ai_with_options(AIDifficulty::Hard, &player_options(1)))
     } else {
         None
     };
     
-    let mut ai2 = if ai_vs_ai && p2_process.is_none() {
-        Some(create_ai(AIDifficulty::Medium))
+    let ai2 = if ai_vs_ai && p2_process.is_none() {
+        Some(create_ai_with_options(AIDifficulty::Medium, &player_options(2)))
     } else {
         None
     };
@@ -116,49 +404,170 @@ fn run_terminal_game(
     println!("$$$ exec p2 : [{}]", 
              if ai_vs_ai && p2_process.is_none() { "AI Medium" }
              else { player2_cmd.as_deref().unwrap_or("AI") });
+    let mut ais = vec![ai1, ai2];
+    for player in 3..=game.num_players {
+        ais.push(Some(create_ai_with_options(AIDifficulty::Medium, &player_options(player))));
+        println!("$$$ exec p{} : [AI Medium]", player);
+    }
+
+    let seats = (1..=game.num_players)
+        .map(|player| match (player, &player1_cmd, &player2_cmd) {
+            (1, Some(cmd), _) | (2, _, Some(cmd)) => format!("bot {}", cmd),
+            (1, None, _) if ai_vs_ai => "ai hard".to_string(),
+            (1, None, _) => "human".to_string(),
+            _ => "ai medium".to_string(),
+        })
+        .collect();
+    let mut session = Session { seed, piece_set: piece_source, preview, seats };
+    // A piece dealt but not played because the human saved or loaded
+    let mut pending_piece = None;
+    let mut replay = Replay::new(&game, session.seed, session.seats.clone());
+
+    if hand_size > 0 && !game.uses_hands() {
+        game.hands = (0..game.num_players)
+            .map(|_| (0..hand_size).map(|_| piece_generator.next()).collect())
+            .collect();
+    }
 
     // Main game loop
     loop {
-        let current_piece = piece_generator.next();
+        if game.uses_hands() {
+            if game.get_hand_moves().is_empty() {
+                let player = game.current_player;
+                let eliminated_before = game.eliminated.clone();
+                let game_over = game.eliminate_current();
+                if let Some(piece) = game.hand(player).first() {
+                    replay.record_dropouts(&eliminated_before, &game, piece);
+                }
+                if game_over {
+                    break;
+                }
+                continue;
+            }
+            display_anfield(&game);
+            display_hand(&game);
+
+            let player = game.current_player;
+            let mut command = None;
+            let hand_move = if let Some(ai) = ais[player as usize - 1].as_mut() {
+                get_ai_hand_move(ai.as_mut(), &game, &mut debug_log)
+            } else if player == 1 {
+                if human_mode && !ai_vs_ai {
+                    get_human_hand_move(&game, &mut command)
+                } else if let Some(ref mut process) = p1_process {
+                    get_bot_hand_move(process, &game, 1)
+                } else {
+                    get_human_hand_move(&game, &mut command)
+                }
+            } else if let Some(ref mut process) = p2_process {
+                get_bot_hand_move(process, &game, 2)
+            } else {
+                create_ai_with_options(AIDifficulty::Medium, &player_options(2)).choose_hand_move(&game)
+            };
+
+            if let Some(command) = command {
+                let dealt = piece_generator.dealt();
+                if session.run(command, &mut game, &mut piece_generator, dealt) {
+                    replay = Replay::new(&game, session.seed, session.seats.clone());
+                }
+                continue;
+            }
+            let Some(hand_move) = hand_move else {
+                println!("Player {} has no valid moves", player);
+                break;
+            };
+            let before = game.clone();
+            let Some(piece) = game.play_hand_move(hand_move) else {
+                println!("Invalid move by player {}", player);
+                break;
+            };
+            println!(
+                "Player {} placed piece {} at ({}, {}) in orientation {}",
+                player,
+                hand_move.piece_index + 1,
+                hand_move.y,
+                hand_move.x,
+                hand_move.orientation
+            );
+            observe_move(&mut ais, player, &before, &game, &piece);
+            replay.record_move(player, &piece, hand_move.x, hand_move.y);
+
+            // Refill the hand the piece came from
+            game.hands[player as usize - 1].push(piece_generator.next());
+            game.switch_player();
+            continue;
+        }
+
+        let current_piece = pending_piece.take().unwrap_or_else(|| piece_generator.next());
+        game.upcoming = piece_generator.preview(session.preview);
         
         // Check if game is over
-        if game.check_game_over(&current_piece) {
+        let eliminated_before = game.eliminated.clone();
+        let game_over = game.check_game_over(&current_piece);
+        replay.record_dropouts(&eliminated_before, &game, &current_piece);
+        if game_over {
             break;
         }
 
         // Display current state
         display_anfield(&game);
         display_piece(&current_piece);
+        display_preview(&game);
 
         // Get move from current player
-        let move_result = if game.current_player == 1 {
+        let mut command = None;
+        let move_result = if let Some(ai) = ais[game.current_player as usize - 1].as_mut() {
+            get_ai_move(ai.as_mut(), &game, &current_piece, &mut debug_log)
+        } else if game.current_player == 1 {
             if human_mode && !ai_vs_ai {
-                get_human_move(&game, &current_piece)
-            } else if let Some(ref mut ai) = ai1 {
-                ai.choose_move(&game, &current_piece)
+                get_human_move(&game, &current_piece, &mut command)
             } else if let Some(ref mut process) = p1_process {
                 get_bot_move(process, &game, &current_piece, 1)
             } else {
-                get_human_move(&game, &current_piece)
+                get_human_move(&game, &current_piece, &mut command)
             }
         } else {
-            if let Some(ref mut ai) = ai2 {
-                ai.choose_move(&game, &current_piece)
-            } else if let Some(ref mut process) = p2_process {
+            if let Some(ref mut process) = p2_process {
                 get_bot_move(process, &game, &current_piece, 2)
             } else {
                 // Default AI if no player 2 specified
-                let mut ai = create_ai(AIDifficulty::Medium);
-                ai.choose_move(&game, &current_piece)
+                let mut ai = create_ai_with_options(AIDifficulty::Medium, &player_options(2));
+                get_ai_move(ai.as_mut(), &game, &current_piece, &mut debug_log)
             }
         };
 
+        if let Some(command) = command {
+            // The current piece is dealt again when the game continues
+            let dealt = piece_generator.dealt() - 1;
+            if session.run(command, &mut game, &mut piece_generator, dealt) {
+                replay = Replay::new(&game, session.seed, session.seats.clone());
+            } else {
+                pending_piece = Some(current_piece);
+            }
+            continue;
+        }
+
         match move_result {
-            Some((x, y)) => {
-                if game.is_valid_move(&current_piece, x, y) {
-                    game.place_piece(&current_piece, x, y);
-                    println!("Player {} placed piece at ({}, {})", game.current_player, y, x);
+            Some(oriented_move) => {
+                if game.is_valid_oriented_move(&current_piece, oriented_move) {
+                    let before = game.clone();
+                    let placed = current_piece.oriented(oriented_move.orientation);
+                    game.place_piece(&placed, oriented_move.x, oriented_move.y);
+                    if oriented_move.orientation == 0 {
+                        println!("Player {} placed piece at ({}, {})", game.current_player, oriented_move.y, oriented_move.x);
+                    } else {
+                        println!("Player {} placed piece at ({}, {}) in orientation {}",
+                                 game.current_player, oriented_move.y, oriented_move.x, oriented_move.orientation);
+                    }
+                    let mover = game.current_player;
+                    observe_move(&mut ais, mover, &before, &game, &placed);
+                    replay.record_move(mover, &placed, oriented_move.x, oriented_move.y);
                     game.switch_player();
+                    
+                    // Let a built-in AI think while the opponent moves
+                    if let Some(ai) = ais[mover as usize - 1].as_mut() {
+                        ai.start_pondering(&game, &current_piece);
+                    }
                 } else {
                     println!("Invalid move by player {}", game.current_player);
                     break;
@@ -173,37 +582,63 @@ fn run_terminal_game(
 
     // Game over
     display_anfield(&game);
-    let (p1_score, p2_score) = game.calculate_scores();
+    let scores = game.player_scores();
     
     println!("== O fin de la partie ==");
-    println!("Player 1: {} points", p1_score);
-    println!("Player 2: {} points", p2_score);
+    for (index, score) in scores.iter().enumerate() {
+        println!("Player {}: {} points", index + 1, score);
+    }
     
-    if p1_score > p2_score {
-        println!("Player 1 WINS!");
-    } else if p2_score > p1_score {
-        println!("Player 2 WINS!");
+    if game.is_team_game() {
+        let team_scores = game.team_scores();
+        for (index, score) in team_scores.iter().enumerate() {
+            println!("Team {}: {} points", index + 1, score);
+        }
+        match unique_leader(&team_scores) {
+            Some(team) => println!("Team {} WINS!", team),
+            None => println!("TIE!"),
+        }
     } else {
-        println!("TIE!");
+        match unique_leader(&scores) {
+            Some(player) => println!("Player {} WINS!", player),
+            None => println!("TIE!"),
+        }
+    }
+
+    if let Some(directory) = replay_dir {
+        match replay.save_in(&directory) {
+            Ok(path) => println!("Replay saved to {}", path.display()),
+            Err(err) => eprintln!("Could not save the replay: {}", err),
+        }
     }
 
     Ok(())
 }
 
+/// Shows `mover`'s move to every other built-in AI.
+fn observe_move(
+    ais: &mut [Option<Box<dyn AIPlayer>>],
+    mover: u8,
+    before: &GameState,
+    after: &GameState,
+    piece: &Piece,
+) {
+    for (index, ai) in ais.iter_mut().enumerate() {
+        if index + 1 != mover as usize {
+            if let Some(ai) = ai {
+                ai.observe_opponent_move(before, after, Some(piece));
+            }
+        }
+    }
+}
+
 fn display_anfield(game: &GameState) {
     println!("Anfield {} {}:", game.height, game.width);
     
     for (y, row) in game.board.iter().enumerate() {
         print!("{:03} ", y);
         for cell in row {
-            let ch = match cell {
-                Cell::Empty => '.',
-                Cell::Player1Old => '@',
-                Cell::Player1New => 'a',
-                Cell::Player2Old => '$',
-                Cell::Player2New => 's',
-            };
-            print!("{}", ch);
+            print!("{}", cell.to_char());
         }
         println!();
     }
@@ -211,7 +646,24 @@ fn display_anfield(game: &GameState) {
 
 fn display_piece(piece: &Piece) {
     println!("Piece {} {}:", piece.height, piece.width);
-    
+    display_piece_rows(piece);
+}
+
+fn display_preview(game: &GameState) {
+    for (index, piece) in game.upcoming.iter().enumerate() {
+        println!("Preview {} {} {}:", index + 1, piece.height, piece.width);
+        display_piece_rows(piece);
+    }
+}
+
+fn display_hand(game: &GameState) {
+    for (index, piece) in game.hand(game.current_player).iter().enumerate() {
+        println!("Hand piece {} {} {}:", index + 1, piece.height, piece.width);
+        display_piece_rows(piece);
+    }
+}
+
+fn display_piece_rows(piece: &Piece) {
     for y in 0..piece.height {
         for x in 0..piece.width {
             if piece.shape.contains(&(x, y)) {
@@ -224,31 +676,142 @@ fn display_piece(piece: &Piece) {
     }
 }
 
-fn get_human_move(game: &GameState, piece: &Piece) -> Option<(usize, usize)> {
+/// Reads a move from the terminal. A `save` or `load` command is stored in
+/// `command` instead, and no move is returned.
+fn get_human_move(game: &GameState, piece: &Piece, command: &mut Option<GameCommand>) -> Option<OrientedMove> {
+    let fixed = game.piece_rules.is_fixed();
+    if !fixed {
+        for orientation in game.piece_rules.orientations().into_iter().skip(1) {
+            println!("Orientation {}:", orientation);
+            display_piece_rows(&piece.oriented(orientation));
+        }
+    }
+
     loop {
-        print!("Enter move (row column): ");
+        if fixed {
+            print!("Enter move (row column), or save/load <file>: ");
+        } else {
+            print!("Enter move (row column [orientation]), or save/load <file>: ");
+        }
         io::stdout().flush().ok();
         
         let mut input = String::new();
         if io::stdin().read_line(&mut input).is_err() {
             continue;
         }
+        if let Some(parsed) = GameCommand::parse(&input) {
+            *command = Some(parsed);
+            return None;
+        }
         
-        let coords: Vec<&str> = input.trim().split_whitespace().collect();
-        if coords.len() == 2 {
-            if let (Ok(row), Ok(col)) = (coords[0].parse::<usize>(), coords[1].parse::<usize>()) {
-                if game.is_valid_move(piece, col, row) {
-                    return Some((col, row));
-                } else {
-                    println!("Invalid move, try again.");
-                }
-            } else {
+        let numbers: Option<Vec<usize>> = input.split_whitespace().map(|part| part.parse().ok()).collect();
+        let oriented_move = match numbers.as_deref() {
+            Some(&[row, col]) => OrientedMove { orientation: 0, x: col, y: row },
+            Some(&[row, col, orientation]) if !fixed => OrientedMove { orientation, x: col, y: row },
+            Some(_) if fixed => {
+                println!("Invalid input format. Use: row column");
+                continue;
+            }
+            _ => {
                 println!("Invalid input format.");
+                continue;
             }
+        };
+        if game.is_valid_oriented_move(piece, oriented_move) {
+            return Some(oriented_move);
+        }
+        println!("Invalid move, try again.");
+    }
+}
+
+fn get_human_hand_move(game: &GameState, command: &mut Option<GameCommand>) -> Option<HandMove> {
+    let fixed = game.piece_rules.is_fixed();
+    if !fixed {
+        for (index, piece) in game.hand(game.current_player).iter().enumerate() {
+            for orientation in game.piece_rules.orientations().into_iter().skip(1) {
+                println!("Hand piece {} orientation {}:", index + 1, orientation);
+                display_piece_rows(&piece.oriented(orientation));
+            }
+        }
+    }
+    loop {
+        if fixed {
+            print!("Enter move (piece row column), or save/load <file>: ");
         } else {
-            println!("Invalid input format. Use: row column");
+            print!("Enter move (piece row column [orientation]), or save/load <file>: ");
+        }
+        io::stdout().flush().ok();
+        
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).is_err() {
+            continue;
+        }
+        if let Some(parsed) = GameCommand::parse(&input) {
+            *command = Some(parsed);
+            return None;
+        }
+        
+        let numbers: Option<Vec<usize>> = input.split_whitespace().map(|part| part.parse().ok()).collect();
+        let hand_move = match numbers.as_deref() {
+            Some(&[piece, row, col]) if piece > 0 => HandMove { piece_index: piece - 1, orientation: 0, x: col, y: row },
+            Some(&[piece, row, col, orientation]) if piece > 0 && !fixed => {
+                HandMove { piece_index: piece - 1, orientation, x: col, y: row }
+            }
+            _ if fixed => {
+                println!("Invalid input format. Use: piece row column");
+                continue;
+            }
+            _ => {
+                println!("Invalid input format. Use: piece row column [orientation]");
+                continue;
+            }
+        };
+        if game.is_valid_hand_move(hand_move) {
+            return Some(hand_move);
         }
+        println!("Invalid move, try again.");
+    }
+}
+
+/// Asks a built-in AI for its move, logging its analysis when enabled.
+fn get_ai_move(
+    ai: &mut dyn AIPlayer,
+    game: &GameState,
+    piece: &Piece,
+    debug_log: &mut Option<File>,
+) -> Option<OrientedMove> {
+    if !game.piece_rules.is_fixed() {
+        return ai.choose_oriented_move(game, piece);
+    }
+    let unrotated = |(x, y)| OrientedMove { orientation: 0, x, y };
+    let Some(log) = debug_log.as_mut() else {
+        return ai.choose_move(game, piece).map(unrotated);
+    };
+
+    let analysis = ai.analyze(game, piece);
+    writeln!(log, "Turn {} - player {} ({})", game.turn, game.current_player, ai.get_name()).ok();
+    if let Some(style) = ai.opponent_style() {
+        writeln!(log, "Opponent style: {:?}", style).ok();
+    }
+    write!(log, "{}", analysis.report(10)).ok();
+    writeln!(log).ok();
+    analysis.best_move.map(unrotated)
+}
+
+/// Hand variant of `get_ai_move`.
+fn get_ai_hand_move(ai: &mut dyn AIPlayer, game: &GameState, debug_log: &mut Option<File>) -> Option<HandMove> {
+    let Some(log) = debug_log.as_mut() else {
+        return ai.choose_hand_move(game);
+    };
+
+    let analysis = ai.analyze_hand(game);
+    writeln!(log, "Turn {} - player {} ({})", game.turn, game.current_player, ai.get_name()).ok();
+    if let Some(style) = ai.opponent_style() {
+        writeln!(log, "Opponent style: {:?}", style).ok();
     }
+    write!(log, "{}", analysis.report(10)).ok();
+    writeln!(log).ok();
+    analysis.best_hand_move
 }
 
 fn spawn_player_process(cmd: &str) -> io::Result<std::process::Child> {
@@ -264,60 +827,99 @@ fn get_bot_move(
     game: &GameState, 
     piece: &Piece, 
     player: u8
-) -> Option<(usize, usize)> {
+) -> Option<OrientedMove> {
     // Send game state to bot
     if let Some(stdin) = process.stdin.as_mut() {
-        // Send player info
-        writeln!(stdin, "$$$ exec p{} : [bot]", player).ok()?;
-        
-        // Send anfield
-        writeln!(stdin, "Anfield {} {}:", game.height, game.width).ok()?;
-        for (y, row) in game.board.iter().enumerate() {
-            write!(stdin, "{:03} ", y).ok()?;
-            for cell in row {
-                let ch = match cell {
-                    Cell::Empty => '.',
-                    Cell::Player1Old => '@',
-                    Cell::Player1New => 'a',
-                    Cell::Player2Old => '$',
-                    Cell::Player2New => 's',
-                };
-                write!(stdin, "{}", ch).ok()?;
-            }
-            writeln!(stdin).ok()?;
-        }
-        
-        // Send piece
-        writeln!(stdin, "Piece {} {}:", piece.height, piece.width).ok()?;
-        for y in 0..piece.height {
-            for x in 0..piece.width {
-                if piece.shape.contains(&(x, y)) {
-                    write!(stdin, "O").ok()?;
-                } else {
-                    write!(stdin, ".").ok()?;
-                }
-            }
-            writeln!(stdin).ok()?;
+        send_anfield(stdin, game, player).ok()?;
+        send_piece(stdin, piece).ok()?;
+        send_preview(stdin, game).ok()?;
+        stdin.flush().ok()?;
+    }
+
+    // Bot outputs in X Y format, followed by the orientation when pieces
+    // may be rotated or mirrored
+    match *read_bot_numbers(process)?.as_slice() {
+        [x, y] => Some(OrientedMove { orientation: 0, x, y }),
+        [x, y, orientation] => Some(OrientedMove { orientation, x, y }),
+        _ => None,
+    }
+}
+
+/// Hand variant: the bot receives its whole hand after the anfield, as a
+/// `Hand <n>:` line followed by `n` piece blocks, and replies
+/// `<index> <x> <y>` with pieces numbered from 0, followed by the
+/// orientation when pieces may be rotated or mirrored.
+fn get_bot_hand_move(process: &mut std::process::Child, game: &GameState, player: u8) -> Option<HandMove> {
+    if let Some(stdin) = process.stdin.as_mut() {
+        send_anfield(stdin, game, player).ok()?;
+        let hand = game.hand(player);
+        writeln!(stdin, "Hand {}:", hand.len()).ok()?;
+        for piece in hand {
+            send_piece(stdin, piece).ok()?;
         }
-        
         stdin.flush().ok()?;
     }
 
-    // Read response from bot with timeout
-    if let Some(stdout) = process.stdout.as_mut() {
-        let mut reader = BufReader::new(stdout);
-        let mut response = String::new();
-        
-        // Simple timeout implementation - in a real implementation you'd use proper async I/O
-        if reader.read_line(&mut response).is_ok() {
-            let coords: Vec<&str> = response.trim().split_whitespace().collect();
-            if coords.len() == 2 {
-                if let (Ok(x), Ok(y)) = (coords[0].parse::<usize>(), coords[1].parse::<usize>()) {
-                    return Some((x, y)); // Bot outputs in X Y format
-                }
+    match *read_bot_numbers(process)?.as_slice() {
+        [piece_index, x, y] => Some(HandMove { piece_index, orientation: 0, x, y }),
+        [piece_index, x, y, orientation] => Some(HandMove { piece_index, orientation, x, y }),
+        _ => None,
+    }
+}
+
+fn send_anfield(stdin: &mut impl Write, game: &GameState, player: u8) -> io::Result<()> {
+    // Send player info
+    writeln!(stdin, "$$$ exec p{} : [bot]", player)?;
+    
+    writeln!(stdin, "Anfield {} {}:", game.height, game.width)?;
+    for (y, row) in game.board.iter().enumerate() {
+        write!(stdin, "{:03} ", y)?;
+        for cell in row {
+            write!(stdin, "{}", cell.to_char())?;
+        }
+        writeln!(stdin)?;
+    }
+    Ok(())
+}
+
+fn send_piece(stdin: &mut impl Write, piece: &Piece) -> io::Result<()> {
+    writeln!(stdin, "Piece {} {}:", piece.height, piece.width)?;
+    send_piece_rows(stdin, piece)
+}
+
+/// Preview variant: after the piece, the bot receives the pieces dealt
+/// next, in order, as `Preview <i> <h> <w>:` blocks numbered from 1 and
+/// drawn like the piece. Every player is sent the same queue. Nothing is
+/// sent in the standard game.
+fn send_preview(stdin: &mut impl Write, game: &GameState) -> io::Result<()> {
+    for (index, piece) in game.upcoming.iter().enumerate() {
+        writeln!(stdin, "Preview {} {} {}:", index + 1, piece.height, piece.width)?;
+        send_piece_rows(stdin, piece)?;
+    }
+    Ok(())
+}
+
+fn send_piece_rows(stdin: &mut impl Write, piece: &Piece) -> io::Result<()> {
+    for y in 0..piece.height {
+        for x in 0..piece.width {
+            if piece.shape.contains(&(x, y)) {
+                write!(stdin, "O")?;
+            } else {
+                write!(stdin, ".")?;
             }
         }
+        writeln!(stdin)?;
     }
+    Ok(())
+}
+
+/// Reads one line of whitespace-separated numbers from the bot.
+fn read_bot_numbers(process: &mut std::process::Child) -> Option<Vec<usize>> {
+    let stdout = process.stdout.as_mut()?;
+    let mut reader = BufReader::new(stdout);
+    let mut response = String::new();
     
-    None
+    // Simple timeout implementation - in a real implementation you'd use proper async I/O
+    reader.read_line(&mut response).ok()?;
+    response.split_whitespace().map(|part| part.parse().ok()).collect()
 }
@@ -0,0 +1,76 @@
+use std::env;
+use std::io;
+use std::process;
+use filler::*;
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 || args.iter().any(|arg| arg == "--help") {
+        println!("Usage: {} <map> [options]", args[0]);
+        println!("Options:");
+        println!("  --self-play <n>      Play n piece seeds per start rotation with identical AIs");
+        println!("  --difficulty <name>  AI for self-play: easy, medium, hard or expert (default medium)");
+        println!("  --torus              Analyse the map with wrap-around edges");
+        return Ok(());
+    }
+
+    let mut map_file = None;
+    let mut self_play = 0;
+    let mut difficulty = AIDifficulty::Medium;
+    let mut topology = Topology::Bounded;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match (args[i].as_str(), value) {
+            ("--self-play", Some(value)) => self_play = value.parse().unwrap_or(0),
+            ("--difficulty", Some(value)) => {
+                difficulty = AIDifficulty::from_name(&value).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("unknown difficulty '{}'", value))
+                })?;
+            }
+            ("--torus", _) => {
+                topology = Topology::Torus;
+                i += 1;
+                continue;
+            }
+            (path, _) if map_file.is_none() && !path.starts_with("--") => {
+                map_file = Some(path.to_string());
+                i += 1;
+                continue;
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    let Some(map_file) = map_file else {
+        eprintln!("No map file given");
+        process::exit(2);
+    };
+    let mut game = match GameState::load_map(&map_file) {
+        Ok(game) => game,
+        Err(err) => {
+            println!("{}: cannot be loaded: {}", map_file, err);
+            process::exit(1);
+        }
+    };
+    game.topology = topology;
+
+    let report = analyze_map(&game);
+    print!("{}", report.to_text());
+
+    if self_play > 0 && report.is_well_formed() {
+        let seeds: Vec<u64> = (1..=self_play).collect();
+        print!("{}", self_play_fairness(&game, &difficulty, &seeds).to_text());
+    }
+
+    if !report.is_well_formed() {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use filler::*;
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.iter().any(|arg| arg == "--help") {
+        println!("Usage: {} [options]", args[0]);
+        println!("Options:");
+        println!("  --width <n>          Board width (default 20)");
+        println!("  --height <n>         Board height (default 15)");
+        println!("  --players <n>        2 or 4 start cells (default 2)");
+        println!("  --symmetry <name>    rotational or mirror (default rotational)");
+        println!("  --obstacles <f>      Fraction of the board walled off (default 0)");
+        println!("  --seed <n>           Generator seed (default 0)");
+        println!("  --count <n>          Number of maps, with seeds seed, seed+1, ... (default 1)");
+        println!("  --output <path>      Map file, or directory when --count > 1 (default: stdout)");
+        return Ok(());
+    }
+
+    let mut spec = MapSpec::new(20, 15);
+    let mut count = 1;
+    let mut output = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match (args[i].as_str(), value) {
+            ("--width", Some(value)) => spec.width = parse_number(&value)?,
+            ("--height", Some(value)) => spec.height = parse_number(&value)?,
+            ("--players", Some(value)) => spec.players = parse_number(&value)?,
+            ("--symmetry", Some(value)) => {
+                spec.symmetry = MapSymmetry::from_name(&value).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("unknown symmetry '{}'", value))
+                })?;
+            }
+            ("--obstacles", Some(value)) => spec.obstacle_density = parse_number(&value)?,
+            ("--seed", Some(value)) => spec.seed = parse_number(&value)?,
+            ("--count", Some(value)) => count = parse_number(&value)?,
+            ("--output", Some(value)) => output = Some(value),
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    if count <= 1 {
+        let map = generate_map(&spec)?;
+        match output {
+            Some(path) => map.save_map(path)?,
+            None => print!("{}", map.to_map_string()),
+        }
+        return Ok(());
+    }
+
+    let directory = output.unwrap_or_else(|| "generated_maps".to_string());
+    fs::create_dir_all(&directory)?;
+    let first_seed = spec.seed;
+    let last_seed = first_seed.checked_add(count - 1).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} maps from seed {} would run past the largest seed", count, first_seed),
+        )
+    })?;
+    for seed in first_seed..=last_seed {
+        spec.seed = seed;
+        let name = format!("map_{}x{}_p{}_{}", spec.width, spec.height, spec.players, seed);
+        generate_map(&spec)?.save_map(Path::new(&directory).join(name))?;
+    }
+    println!("{} maps written to {}", count, directory);
+
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid number '{}'", value)))
+}
@@ -0,0 +1,416 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+use std::time::{Duration, Instant};
+use filler::*;
+
+/// Delays between moves while playing, slowest first.
+const SPEEDS_MS: [u64; 6] = [2000, 1000, 500, 250, 100, 30];
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 || args.iter().any(|arg| arg == "--help") {
+        print!("{}", usage(&args[0]));
+        return Ok(());
+    }
+
+    let mut replay_file = None;
+    let mut start_turn = None;
+    let mut print_only = false;
+    let mut import = false;
+    let mut output = None;
+    let mut svg_dir = None;
+    let mut gif_file = None;
+    let mut html_file = None;
+    let mut export = ExportOptions::default();
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match (args[i].as_str(), value) {
+            ("--turn", Some(value)) => {
+                start_turn = Some(value.parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("invalid move number '{}'", value))
+                })?);
+            }
+            ("--output", Some(value)) => output = Some(value),
+            ("--svg", Some(value)) => svg_dir = Some(value),
+            ("--gif", Some(value)) => gif_file = Some(value),
+            ("--html", Some(value)) => html_file = Some(value),
+            ("--cell", Some(value)) => export.cell_size = parse_number(&value)?,
+            ("--delay", Some(value)) => export.frame_delay_ms = parse_number(&value)?,
+            ("--every", Some(value)) => export.every = parse_number(&value)?,
+            ("--print", _) => {
+                print_only = true;
+                i += 1;
+                continue;
+            }
+            ("--import", _) => {
+                import = true;
+                i += 1;
+                continue;
+            }
+            (path, _) if replay_file.is_none() && !path.starts_with("--") => {
+                replay_file = Some(path.to_string());
+                i += 1;
+                continue;
+            }
+            ("--turn" | "--output" | "--svg" | "--gif" | "--html" | "--cell" | "--delay" | "--every", None) => {
+                eprintln!("{} needs a value", args[i]);
+                eprint!("{}", usage(&args[0]));
+                process::exit(2);
+            }
+            (other, _) => {
+                eprintln!("Unknown argument '{}'", other);
+                eprint!("{}", usage(&args[0]));
+                process::exit(2);
+            }
+        }
+        i += 2;
+    }
+
+    let Some(replay_file) = replay_file else {
+        eprintln!("No replay file given");
+        process::exit(2);
+    };
+    let replay = if import { load_engine_log(&replay_file)? } else { Replay::load(&replay_file)? };
+    let states = replay.states()?;
+
+    if output.is_some() || svg_dir.is_some() || gif_file.is_some() || html_file.is_some() {
+        if let Some(path) = output {
+            replay.save(&path)?;
+            println!("{} moves written to {}", replay.moves.len(), path);
+        }
+        if let Some(directory) = svg_dir {
+            fs::create_dir_all(&directory)?;
+            let frames = replay_svg_frames(&replay, &export)?;
+            for (index, frame) in frames.iter().enumerate() {
+                fs::write(Path::new(&directory).join(format!("frame_{:04}.svg", index)), frame)?;
+            }
+            println!("{} frames written to {}", frames.len(), directory);
+        }
+        if let Some(path) = gif_file {
+            fs::write(&path, replay_gif(&replay, &export)?)?;
+            println!("Animation written to {}", path);
+        }
+        if let Some(path) = html_file {
+            fs::write(&path, replay_html(&replay)?)?;
+            println!("Web page written to {}", path);
+        }
+        return Ok(());
+    }
+
+    if print_only {
+        let mut viewer = Viewer::new(&replay_file, &replay, &states);
+        viewer.turn = start_turn.unwrap_or(viewer.last()).min(viewer.last());
+        print!("{}", viewer.frame(false));
+        return Ok(());
+    }
+
+    let terminal = match RawTerminal::enable() {
+        Ok(terminal) => terminal,
+        Err(err) => {
+            eprintln!("Cannot control the terminal ({}); use --print to show a position", err);
+            process::exit(1);
+        }
+    };
+    let mut viewer = Viewer::new(&replay_file, &replay, &states);
+    viewer.turn = start_turn.unwrap_or(0).min(viewer.last());
+    let result = viewer.run();
+    drop(terminal);
+    result
+}
+
+/// The `--help` text.
+fn usage(program: &str) -> String {
+    let mut text = format!("Usage: {} <replay> [options]\n", program);
+    text.push_str("Options:\n");
+    text.push_str("  --turn <n>           Start at move n (default 0, the starting position)\n");
+    text.push_str("  --print              Print the position at --turn (default: the end) and exit\n");
+    text.push_str("  --import             Read the file as a captured official engine log\n");
+    text.push_str("  --output <file>      Write the replay (e.g. an imported log) to a file and exit\n");
+    text.push_str("  --svg <dir>          Write one SVG image per move to a directory and exit\n");
+    text.push_str("  --gif <file>         Write the game as an animated GIF and exit\n");
+    text.push_str("  --html <file>        Write a web page that plays the game offline and exit\n");
+    text.push_str("  --cell <px>          Cell size for --svg and --gif (default 12)\n");
+    text.push_str("  --delay <ms>         Time per frame in the GIF (default 200)\n");
+    text.push_str("  --every <n>          Draw every n-th move only (default 1)\n");
+    text.push('\n');
+    text.push_str("Keys:\n");
+    text.push_str("  Right/l, Left/h      Step one move forward or back\n");
+    text.push_str("  Up/k, Down/j         Step ten moves forward or back\n");
+    text.push_str("  Home/0, End/$        First or last position\n");
+    text.push_str("  g <n> Enter          Jump to move n\n");
+    text.push_str("  Space                Play or pause\n");
+    text.push_str("  +/-                  Play faster or slower\n");
+    text.push_str("  r                    Restart from the first position, paused\n");
+    text.push_str("  q/Esc                Quit\n");
+    text
+}
+
+/// Puts the terminal into unbuffered, silent input mode on an alternate
+/// screen, and puts it back when dropped, also when unwinding from a panic.
+/// Uses `stty` on the controlling terminal.
+struct RawTerminal {
+    saved: String,
+}
+
+impl RawTerminal {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?.trim().to_string();
+        // Keys arrive one at a time without echo; reads give up after 0.1s
+        // so playback can advance. Ctrl-C is read as a key, so quitting
+        // always goes through Drop.
+        stty(&["-icanon", "-echo", "-isig", "min", "0", "time", "1"])?;
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        io::stdout().flush().ok();
+        stty(&[self.saved.as_str()]).ok();
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::new(io::ErrorKind::Other, message));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Escape,
+    Interrupt,
+}
+
+/// Splits the bytes of one read into keys, decoding arrow, Home and End
+/// escape sequences.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let key = match bytes[i] {
+            0x1b if bytes.get(i + 1).is_some_and(|&b| b == b'[' || b == b'O') => {
+                // CSI or SS3: parameters, then a final letter or '~'
+                let mut end = i + 2;
+                while end < bytes.len() && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+                let params = &bytes[i + 2..end];
+                let key = match (bytes.get(end), params) {
+                    (Some(b'A'), _) => Some(Key::Up),
+                    (Some(b'B'), _) => Some(Key::Down),
+                    (Some(b'C'), _) => Some(Key::Right),
+                    (Some(b'D'), _) => Some(Key::Left),
+                    (Some(b'H'), _) | (Some(b'~'), b"1") | (Some(b'~'), b"7") => Some(Key::Home),
+                    (Some(b'F'), _) | (Some(b'~'), b"4") | (Some(b'~'), b"8") => Some(Key::End),
+                    _ => None,
+                };
+                i = end + 1;
+                if let Some(key) = key {
+                    keys.push(key);
+                }
+                continue;
+            }
+            0x1b => Key::Escape,
+            0x03 => Key::Interrupt,
+            b'\r' | b'\n' => Key::Enter,
+            0x7f | 0x08 => Key::Backspace,
+            byte => Key::Char(byte as char),
+        };
+        keys.push(key);
+        i += 1;
+    }
+    keys
+}
+
+struct Viewer<'a> {
+    file: &'a str,
+    replay: &'a Replay,
+    states: &'a [GameState],
+    /// Index into `states`: 0 is the start, n is after move n.
+    turn: usize,
+    playing: bool,
+    speed: usize,
+    /// Digits typed after `g`, while a jump is being entered.
+    jump: Option<String>,
+}
+
+impl<'a> Viewer<'a> {
+    fn new(file: &'a str, replay: &'a Replay, states: &'a [GameState]) -> Self {
+        Self {
+            file,
+            replay,
+            states,
+            turn: 0,
+            playing: false,
+            speed: 1,
+            jump: None,
+        }
+    }
+
+    fn last(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    fn go_to(&mut self, turn: usize) {
+        self.turn = turn.min(self.last());
+    }
+
+    fn delay(&self) -> Duration {
+        Duration::from_millis(SPEEDS_MS[self.speed])
+    }
+
+    fn run(&mut self) -> io::Result<()> {
+        let mut stdin = io::stdin().lock();
+        let mut buffer = [0u8; 64];
+        let mut last_step = Instant::now();
+        let mut shown = None;
+
+        loop {
+            let view = (self.turn, self.playing, self.speed, self.jump.clone());
+            if shown.as_ref() != Some(&view) {
+                print!("\x1b[H\x1b[2J{}", self.frame(true));
+                io::stdout().flush()?;
+                shown = Some(view);
+            }
+
+            let read = stdin.read(&mut buffer)?;
+            for key in parse_keys(&buffer[..read]) {
+                if !self.handle_key(key) {
+                    return Ok(());
+                }
+                last_step = Instant::now();
+            }
+
+            if self.playing && last_step.elapsed() >= self.delay() {
+                if self.turn < self.last() {
+                    self.turn += 1;
+                }
+                if self.turn == self.last() {
+                    self.playing = false;
+                }
+                last_step = Instant::now();
+            }
+        }
+    }
+
+    /// Applies one key. Returns false when the viewer should quit.
+    fn handle_key(&mut self, key: Key) -> bool {
+        if let Some(digits) = self.jump.as_mut() {
+            match key {
+                Key::Char(c) if c.is_ascii_digit() => digits.push(c),
+                Key::Backspace => {
+                    digits.pop();
+                }
+                Key::Enter => {
+                    if let Ok(turn) = digits.parse() {
+                        self.go_to(turn);
+                    }
+                    self.jump = None;
+                }
+                Key::Interrupt => return false,
+                _ => self.jump = None,
+            }
+            return true;
+        }
+
+        match key {
+            Key::Char('q') | Key::Escape | Key::Interrupt => return false,
+            Key::Right | Key::Char('l') | Key::Char('d') => self.go_to(self.turn + 1),
+            Key::Left | Key::Char('h') | Key::Char('a') => self.go_to(self.turn.saturating_sub(1)),
+            Key::Up | Key::Char('k') => self.go_to(self.turn + 10),
+            Key::Down | Key::Char('j') => self.go_to(self.turn.saturating_sub(10)),
+            Key::Home | Key::Char('0') => self.go_to(0),
+            Key::End | Key::Char('$') => self.go_to(self.last()),
+            Key::Char('g') => self.jump = Some(String::new()),
+            Key::Char(' ') => {
+                if self.turn == self.last() {
+                    self.turn = 0;
+                }
+                self.playing = !self.playing;
+            }
+            Key::Char('+') | Key::Char('=') => self.speed = (self.speed + 1).min(SPEEDS_MS.len() - 1),
+            Key::Char('-') | Key::Char('_') => self.speed = self.speed.saturating_sub(1),
+            Key::Char('r') => {
+                self.turn = 0;
+                self.playing = false;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// The screen for the current position. `controls` adds the status and
+    /// key lines shown in the interactive viewer.
+    fn frame(&self, controls: bool) -> String {
+        let state = &self.states[self.turn];
+        let mut text = format!("{}  seed {}\n", self.file, self.replay.seed);
+        for (index, seat) in self.replay.players.iter().enumerate() {
+            text.push_str(&format!("Player {} ({}): {}\n", index + 1, Cell::old_for(index as u8 + 1), seat));
+        }
+        text.push_str(&format!("\nMove {}/{}", self.turn, self.last()));
+        match self.turn.checked_sub(1).map(|index| &self.replay.moves[index]) {
+            Some(ReplayMove { player, position: Some((x, y)), .. }) => {
+                text.push_str(&format!(": player {} placed at ({}, {})\n", player, y, x));
+            }
+            Some(ReplayMove { player, position: None, .. }) => {
+                text.push_str(&format!(": player {} could not move and is out\n", player));
+            }
+            None => text.push_str(": starting position\n"),
+        }
+
+        let scores: Vec<String> = state
+            .player_scores()
+            .iter()
+            .enumerate()
+            .map(|(index, score)| format!("P{} {}", index + 1, score))
+            .collect();
+        text.push_str(&format!("Scores: {}\n\n", scores.join("  ")));
+        text.push_str(&state.display_board());
+
+        if self.turn == self.last() {
+            text.push_str(&format!("\n{}\n", state.result_text()));
+        }
+        if let Some(next) = self.replay.moves.get(self.turn) {
+            text.push_str(&format!("\nNext piece (player {}):\n{}", next.player, next.piece.display()));
+        }
+
+        if controls {
+            let status = if self.playing { "Playing" } else { "Paused" };
+            text.push_str(&format!("\n{} at {}ms per move", status, SPEEDS_MS[self.speed]));
+            match &self.jump {
+                Some(digits) => text.push_str(&format!("   Go to move: {}_\n", digits)),
+                None => text.push('\n'),
+            }
+            text.push_str("←/→ step  ↑/↓ ten  Home/End  g jump  Space play  +/- speed  r restart  q quit\n");
+        }
+        text
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid number '{}'", value)))
+}
@@ -0,0 +1,153 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use filler::*;
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.iter().any(|arg| arg == "--help") {
+        println!("Usage: {} [options]", args[0]);
+        println!("Options:");
+        println!("  -f <map>             Tune on map file (repeatable, default: the bundled 2-player maps)");
+        println!("  --difficulty <name>  AI to tune: medium, hard or expert (default medium)");
+        println!("  --generations <n>    Number of SPSA generations (default 20)");
+        println!("  --seeds <n>          Piece seeds per map (default 4)");
+        println!("  --seed <n>           Tuner random seed (default 0)");
+        println!("  --weights <file>     Starting weights");
+        println!("  --output <file>      Tuned weights file (default tuned_weights.toml)");
+        println!("  --log <file>         Generation log (default tune.log)");
+        return Ok(());
+    }
+
+    let mut map_files = Vec::new();
+    let mut difficulty = AIDifficulty::Medium;
+    let mut generations = None;
+    let mut seed_count = None;
+    let mut rng_seed = 0;
+    let mut weights_file = None;
+    let mut output_file = "tuned_weights.toml".to_string();
+    let mut log_file = "tune.log".to_string();
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match (args[i].as_str(), value) {
+            ("-f", Some(value)) => map_files.push(value),
+            ("--difficulty", Some(value)) => {
+                difficulty = AIDifficulty::from_name(&value).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("unknown difficulty '{}'", value))
+                })?;
+            }
+            ("--generations", Some(value)) => generations = value.parse().ok(),
+            ("--seeds", Some(value)) => seed_count = value.parse::<u64>().ok(),
+            ("--seed", Some(value)) => rng_seed = value.parse().unwrap_or(0),
+            ("--weights", Some(value)) => weights_file = Some(value),
+            ("--output", Some(value)) => output_file = value,
+            ("--log", Some(value)) => log_file = value,
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    let maps = if map_files.is_empty() {
+        bundled_maps()?
+    } else {
+        map_files.iter().map(GameState::load_map).collect::<io::Result<Vec<_>>>()?
+    };
+
+    let mut weights_config = match weights_file {
+        Some(path) => WeightsConfig::load(path)?,
+        None => WeightsConfig::default(),
+    };
+
+    // Tune the size-specific set when every map has the same dimensions
+    let first_size = (maps[0].width, maps[0].height);
+    let single_size = maps.iter().all(|map| (map.width, map.height) == first_size);
+    let initial = if single_size {
+        weights_config.for_board(first_size.0, first_size.1).clone()
+    } else {
+        weights_config.default.clone()
+    };
+
+    let mut config = TunerConfig::new(difficulty, maps)?;
+    config.rng_seed = rng_seed;
+    if let Some(generations) = generations {
+        config.generations = generations;
+    }
+    if let Some(count) = seed_count {
+        config.piece_seeds = (1..=count).collect();
+    }
+
+    println!("Tuning {:?} over {} map(s), {} seed(s), {} generation(s)",
+             config.difficulty, config.maps.len(), config.piece_seeds.len(), config.generations);
+    println!("Parameters: {}", config.parameters.join(", "));
+
+    let mut log = TeeWriter {
+        file: File::create(&log_file)?,
+    };
+    let mut tuner = Tuner::new(config, initial);
+    let tuned = tuner.run(&mut log)?;
+
+    if single_size {
+        weights_config.set_for_size(first_size.0, first_size.1, tuned);
+    } else {
+        weights_config.default = tuned;
+    }
+    weights_config.save(&output_file)?;
+
+    println!("Tuned weights written to {}", output_file);
+    println!("Generation log written to {}", log_file);
+
+    Ok(())
+}
+
+/// Every two-player map in the bundled map directories, in name order.
+fn bundled_maps() -> io::Result<Vec<GameState>> {
+    let mut paths = Vec::new();
+    for directory in BUNDLED_MAP_DIRS {
+        if let Ok(entries) = fs::read_dir(directory) {
+            paths.extend(entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.is_file()));
+        }
+    }
+    paths.sort();
+
+    let mut maps = Vec::new();
+    for path in paths {
+        let map = GameState::load_map(&path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        if map.num_players == 2 {
+            maps.push(map);
+        }
+    }
+    if maps.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no two-player maps in {}; pass maps with -f", BUNDLED_MAP_DIRS.join(" or ")),
+        ));
+    }
+    Ok(maps)
+}
+
+const BUNDLED_MAP_DIRS: [&str; 2] = ["maps", "docker_image/maps"];
+
+/// Mirrors the generation log to stdout while writing it to disk.
+struct TeeWriter {
+    file: File,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write_all(buf)?;
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()?;
+        self.file.flush()
+    }
+}
@@ -0,0 +1,684 @@
+use std::collections::HashMap;
+use std::io;
+use crate::{Cell, GameState, Replay};
+
+/// How `replay_svg_frames` and `replay_gif` draw a replay.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Side of one board cell in pixels.
+    pub cell_size: usize,
+    /// How long each frame is shown in the GIF, in milliseconds.
+    pub frame_delay_ms: u32,
+    /// Moves per frame: 1 draws every move, larger values skip ahead. The
+    /// last position is always drawn.
+    pub every: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 12,
+            frame_delay_ms: 200,
+            every: 1,
+        }
+    }
+}
+
+/// Colours as RGB; the index is the GIF palette entry.
+const PALETTE: [[u8; 3]; 16] = [
+    [0xf4, 0xf4, 0xf4], // empty cell
+    [0x33, 0x33, 0x33], // wall
+    [0x22, 0x22, 0x22], // text
+    [0xff, 0xff, 0xff], // score panel
+    [0xd6, 0x45, 0x2a], // player 1
+    [0x2b, 0x6c, 0xb8], // player 2
+    [0x2f, 0x9e, 0x5c], // player 3
+    [0xd9, 0x96, 0x00], // player 4
+    [0xff, 0x9e, 0x80], // player 1, latest piece
+    [0x8c, 0xc4, 0xff], // player 2, latest piece
+    [0x8e, 0xe6, 0xa9], // player 3, latest piece
+    [0xff, 0xd8, 0x66], // player 4, latest piece
+    [0xd0, 0xd0, 0xd0], // grid lines
+    [0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00],
+];
+const EMPTY: u8 = 0;
+const WALL: u8 = 1;
+const TEXT: u8 = 2;
+const PANEL: u8 = 3;
+const GRID: u8 = 12;
+
+fn cell_colour(cell: &Cell) -> u8 {
+    match (cell.owner(), cell) {
+        (Some(owner), cell) if cell.is_new() => 7 + owner,
+        (Some(owner), _) => 3 + owner,
+        (None, Cell::Blocked) => WALL,
+        (None, _) => EMPTY,
+    }
+}
+
+fn hex(colour: u8) -> String {
+    let [r, g, b] = PALETTE[colour as usize];
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// The positions to draw: every `every`-th one and the last.
+fn frame_indices(states: usize, every: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..states).step_by(every.max(1)).collect();
+    if indices.last() != Some(&(states - 1)) {
+        indices.push(states - 1);
+    }
+    indices
+}
+
+/// Height of the score strip above the board, in pixels.
+fn panel_height(cell_size: usize) -> usize {
+    (cell_size * 2).max(FONT_HEIGHT * 2 + 8)
+}
+
+/// One SVG image per drawn position: the board in player colours with the
+/// latest piece lightened and outlined, under a strip with the move number
+/// and every player's score.
+pub fn replay_svg_frames(replay: &Replay, options: &ExportOptions) -> io::Result<Vec<String>> {
+    let states = replay.states()?;
+    let total = replay.moves.len();
+    Ok(frame_indices(states.len(), options.every)
+        .into_iter()
+        .map(|index| svg_frame(&states[index], index, total, options.cell_size))
+        .collect())
+}
+
+/// One position as an SVG image; `turn` of `total` moves are played.
+pub fn svg_frame(game: &GameState, turn: usize, total: usize, cell_size: usize) -> String {
+    let panel = panel_height(cell_size);
+    let (width, height) = (game.width * cell_size, game.height * cell_size + panel);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", width, panel, hex(PANEL)));
+    svg.push_str(&format!(
+        "<rect y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        panel,
+        width,
+        height - panel,
+        hex(EMPTY)
+    ));
+
+    // Latest cells go last so their outlines are not painted over
+    let mut latest = String::new();
+    for (y, row) in game.board.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            let colour = cell_colour(cell);
+            let (px, py) = (x * cell_size, panel + y * cell_size);
+            let (stroke, stroke_width) = if cell.is_new() { (TEXT, 2.0) } else { (GRID, 0.5) };
+            let rect = format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                px,
+                py,
+                cell_size,
+                cell_size,
+                hex(colour),
+                hex(stroke),
+                stroke_width
+            );
+            if cell.is_new() {
+                latest.push_str(&rect);
+            } else {
+                svg.push_str(&rect);
+            }
+        }
+    }
+    svg.push_str(&latest);
+
+    let font_size = panel * 3 / 5;
+    let baseline = panel / 2 + font_size * 2 / 5;
+    svg.push_str(&format!(
+        "<text x=\"6\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" fill=\"{}\">{}/{}",
+        baseline,
+        font_size,
+        hex(TEXT),
+        turn,
+        total
+    ));
+    for (index, score) in game.player_scores().iter().enumerate() {
+        svg.push_str(&format!(
+            "<tspan dx=\"{}\" fill=\"{}\" font-weight=\"bold\">P{} {}</tspan>",
+            font_size,
+            hex(4 + index as u8),
+            index + 1,
+            score
+        ));
+    }
+    svg.push_str("</text>\n</svg>\n");
+    svg
+}
+
+/// The whole replay as an animated GIF, looping, holding the last position
+/// for a few seconds.
+pub fn replay_gif(replay: &Replay, options: &ExportOptions) -> io::Result<Vec<u8>> {
+    let states = replay.states()?;
+    let total = replay.moves.len();
+    let cell_size = options.cell_size.max(2);
+    let first = &states[0];
+    let (width, height) = (first.width * cell_size, first.height * cell_size + panel_height(cell_size));
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "image too large for a GIF; use a smaller cell size"));
+    }
+
+    let mut gif = Vec::new();
+    gif.extend_from_slice(b"GIF89a");
+    gif.extend_from_slice(&(width as u16).to_le_bytes());
+    gif.extend_from_slice(&(height as u16).to_le_bytes());
+    // Global colour table of 16 entries, 8 bits per primary
+    gif.extend_from_slice(&[0xf3, 0, 0]);
+    for colour in PALETTE {
+        gif.extend_from_slice(&colour);
+    }
+    // Loop forever
+    gif.extend_from_slice(&[0x21, 0xff, 0x0b]);
+    gif.extend_from_slice(b"NETSCAPE2.0");
+    gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    let indices = frame_indices(states.len(), options.every);
+    for (position, &index) in indices.iter().enumerate() {
+        let pixels = gif_frame(&states[index], index, total, cell_size);
+        let mut delay = (options.frame_delay_ms / 10).max(1);
+        if position + 1 == indices.len() {
+            delay = delay.max(300);
+        }
+        let delay = delay.min(u16::MAX as u32) as u16;
+        gif.extend_from_slice(&[0x21, 0xf9, 0x04, 0x04]);
+        gif.extend_from_slice(&delay.to_le_bytes());
+        gif.extend_from_slice(&[0x00, 0x00]);
+
+        gif.push(0x2c);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&(width as u16).to_le_bytes());
+        gif.extend_from_slice(&(height as u16).to_le_bytes());
+        gif.push(0);
+        gif.push(GIF_MIN_CODE_SIZE);
+        for block in lzw_encode(&pixels).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0);
+    }
+    gif.push(0x3b);
+    Ok(gif)
+}
+
+/// Palette indices of one GIF frame, row by row.
+fn gif_frame(game: &GameState, turn: usize, total: usize, cell_size: usize) -> Vec<u8> {
+    let panel = panel_height(cell_size);
+    let width = game.width * cell_size;
+    let height = game.height * cell_size + panel;
+    let mut pixels = vec![PANEL; width * height];
+
+    for (y, row) in game.board.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            let colour = cell_colour(cell);
+            for dy in 0..cell_size {
+                for dx in 0..cell_size {
+                    let edge = dx == 0 || dy == 0;
+                    let outline = cell.is_new() && (dx == 0 || dy == 0 || dx == cell_size - 1 || dy == cell_size - 1);
+                    let pixel = match (outline, edge) {
+                        (true, _) => TEXT,
+                        (false, true) => GRID,
+                        (false, false) => colour,
+                    };
+                    pixels[(panel + y * cell_size + dy) * width + x * cell_size + dx] = pixel;
+                }
+            }
+        }
+    }
+
+    // Score strip: move counter, then a swatch and the score of each player
+    let scale = (panel - 4) / FONT_HEIGHT / 2;
+    let scale = scale.max(1);
+    let top = (panel - FONT_HEIGHT * scale) / 2;
+    let mut canvas = Canvas { pixels: &mut pixels, width, height: panel };
+    let mut x = 4;
+    x = canvas.text(&format!("{}/{}", turn, total), x, top, scale, TEXT);
+    for (index, score) in game.player_scores().iter().enumerate() {
+        x += FONT_WIDTH * scale * 2;
+        let swatch = FONT_HEIGHT * scale;
+        canvas.fill(x, top, swatch, swatch, 4 + index as u8);
+        x = canvas.text(&score.to_string(), x + swatch + scale * 2, top, scale, 4 + index as u8);
+    }
+    pixels
+}
+
+struct Canvas<'a> {
+    pixels: &'a mut [u8],
+    width: usize,
+    height: usize,
+}
+
+impl Canvas<'_> {
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, colour: u8) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                self.pixels[py * self.width + px] = colour;
+            }
+        }
+    }
+
+    /// Draws `text` in the built-in font and returns the x after it.
+    fn text(&mut self, text: &str, mut x: usize, y: usize, scale: usize, colour: u8) -> usize {
+        for ch in text.chars() {
+            let glyph = glyph(ch);
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..FONT_WIDTH {
+                    if bits & (1 << (FONT_WIDTH - 1 - column)) != 0 {
+                        self.fill(x + column * scale, y + row * scale, scale, scale, colour);
+                    }
+                }
+            }
+            x += (FONT_WIDTH + 1) * scale;
+        }
+        x
+    }
+}
+
+const FONT_WIDTH: usize = 3;
+const FONT_HEIGHT: usize = 5;
+
+/// A 3x5 glyph, one row of bits per line, for digits and `/`.
+fn glyph(ch: char) -> [u8; FONT_HEIGHT] {
+    match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b011, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => [0; FONT_HEIGHT],
+    }
+}
+
+/// Bits per palette index: 16 colours.
+const GIF_MIN_CODE_SIZE: u8 = 4;
+
+/// GIF's variable-width LZW compression of palette indices.
+fn lzw_encode(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << GIF_MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut code_size = GIF_MIN_CODE_SIZE as u32 + 1;
+    let mut next_code = end + 1;
+
+    writer.write(clear, code_size);
+    let Some((&first, rest)) = pixels.split_first() else {
+        writer.write(end, code_size);
+        return writer.finish();
+    };
+    let mut prefix = first as u16;
+    for &pixel in rest {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+        writer.write(prefix, code_size);
+        if next_code < 4096 {
+            // The decoder widens its codes once the table fills the current
+            // width, one entry behind the encoder
+            if next_code >= 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+            table.insert((prefix, pixel), next_code);
+            next_code += 1;
+        } else {
+            writer.write(clear, code_size);
+            table.clear();
+            code_size = GIF_MIN_CODE_SIZE as u32 + 1;
+            next_code = end + 1;
+        }
+        prefix = pixel as u16;
+    }
+    writer.write(prefix, code_size);
+    if next_code >= 1 << code_size && code_size < 12 {
+        code_size += 1;
+    }
+    writer.write(end, code_size);
+    writer.finish()
+}
+
+/// Packs codes least significant bit first, as GIF expects.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// The replay as a single HTML page that needs nothing else: the game data
+/// is embedded as JSON and a small script draws the board with a move
+/// slider, play/pause, the latest piece highlighted and a chart of every
+/// player's score over the game.
+pub fn replay_html(replay: &Replay) -> io::Result<String> {
+    let states = replay.states()?;
+    let first = &states[0];
+
+    let board: String = first
+        .board
+        .iter()
+        .flatten()
+        .map(|cell| match (cell.owner(), cell) {
+            (Some(owner), _) => char::from(b'0' + owner),
+            (None, Cell::Blocked) => '#',
+            (None, _) => '.',
+        })
+        .collect();
+    // Each move as [player, cell, cell, ...] with cells numbered row by row;
+    // a player dropping out has no cells
+    let moves: Vec<String> = replay
+        .moves
+        .iter()
+        .zip(&states[1..])
+        .map(|(replay_move, after)| {
+            let mut fields = vec![replay_move.player.to_string()];
+            if replay_move.position.is_some() {
+                for (index, cell) in after.board.iter().flatten().enumerate() {
+                    if cell.is_new() {
+                        fields.push(index.to_string());
+                    }
+                }
+            }
+            format!("[{}]", fields.join(","))
+        })
+        .collect();
+    let scores: Vec<String> = states
+        .iter()
+        .map(|state| {
+            let scores: Vec<String> = state.player_scores().iter().map(u32::to_string).collect();
+            format!("[{}]", scores.join(","))
+        })
+        .collect();
+    let players: Vec<String> = (0..first.num_players as usize)
+        .map(|index| {
+            let seat = replay.players.get(index).map_or("", String::as_str);
+            json_string(format!("Player {} {}", index + 1, seat).trim_end())
+        })
+        .collect();
+    let colours: Vec<String> = (0..4).map(|index| json_string(&hex(4 + index))).collect();
+    let latest: Vec<String> = (0..4).map(|index| json_string(&hex(8 + index))).collect();
+    let last = states.last().unwrap_or(first);
+    let result = last.result_text();
+
+    let data = format!(
+        "{{\"width\":{},\"height\":{},\"seed\":{},\"players\":[{}],\"colours\":[{}],\"latest\":[{}],\
+         \"empty\":{},\"wall\":{},\"board\":{},\"moves\":[{}],\"scores\":[{}],\"result\":{}}}",
+        first.width,
+        first.height,
+        json_string(&replay.seed.to_string()),
+        players.join(","),
+        colours.join(","),
+        latest.join(","),
+        json_string(&hex(EMPTY)),
+        json_string(&hex(WALL)),
+        json_string(&board),
+        moves.join(","),
+        scores.join(","),
+        json_string(&result)
+    );
+    Ok(HTML_TEMPLATE
+        .replace("__TITLE__", &format!("Filler replay, seed {}", replay.seed))
+        .replace("__DATA__", &data))
+}
+
+/// `text` as a JSON string that is also safe inside a `<script>` element.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '<' => quoted.push_str("\\u003c"),
+            '>' => quoted.push_str("\\u003e"),
+            '&' => quoted.push_str("\\u0026"),
+            ch if (ch as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>__TITLE__</title>
+<style>
+  body { font-family: sans-serif; margin: 16px; color: #222; background: #fafafa; }
+  h1 { font-size: 18px; margin: 0 0 8px; }
+  #board { border: 1px solid #ccc; background: #fff; image-rendering: pixelated; }
+  #chart { border: 1px solid #ccc; background: #fff; margin-top: 8px; cursor: pointer; }
+  .controls { display: flex; align-items: center; gap: 6px; margin: 8px 0; }
+  .controls input[type=range] { flex: 1; }
+  #scores span { display: inline-block; margin-right: 14px; font-weight: bold; }
+  #status { margin: 4px 0; min-height: 1.2em; }
+</style>
+</head>
+<body>
+<h1>__TITLE__</h1>
+<div id="scores"></div>
+<div id="status"></div>
+<canvas id="board"></canvas>
+<div class="controls">
+  <button id="first" title="First position (Home)">&#x23EE;</button>
+  <button id="back" title="Previous move (Left)">&#x25C0;</button>
+  <button id="play" title="Play or pause (Space)">&#x25B6;</button>
+  <button id="forward" title="Next move (Right)">&#x25B6;&#x25B6;</button>
+  <button id="last" title="Last position (End)">&#x23ED;</button>
+  <input id="slider" type="range" min="0" value="0">
+  <span id="counter"></span>
+  <select id="speed" title="Time per move">
+    <option value="1000">1 s</option>
+    <option value="500">0.5 s</option>
+    <option value="250" selected>0.25 s</option>
+    <option value="100">0.1 s</option>
+    <option value="30">fast</option>
+  </select>
+</div>
+<canvas id="chart" height="160"></canvas>
+<script>
+const game = __DATA__;
+const total = game.moves.length;
+const cellCount = game.width * game.height;
+const keyEvery = 50;
+const keyframes = [];
+
+// The initial board as 0 (empty), 1-4 (players) and 255 (wall)
+(function () {
+  const start = new Uint8Array(cellCount);
+  for (let i = 0; i < cellCount; i++) {
+    const c = game.board[i];
+    start[i] = c === '.' ? 0 : c === '#' ? 255 : Number(c);
+  }
+  keyframes.push(start);
+  let board = start;
+  for (let m = 0; m < total; m++) {
+    if ((m + 1) % keyEvery === 0) {
+      board = board.slice();
+      applyMoves(board, m + 1 - keyEvery, m + 1);
+      keyframes.push(board);
+    }
+  }
+})();
+
+function applyMoves(board, from, to) {
+  for (let m = from; m < to; m++) {
+    const move = game.moves[m];
+    for (let i = 1; i < move.length; i++) board[move[i]] = move[0];
+  }
+}
+
+function boardAt(turn) {
+  const key = Math.floor(turn / keyEvery);
+  const board = keyframes[key].slice();
+  applyMoves(board, key * keyEvery, turn);
+  return board;
+}
+
+const boardCanvas = document.getElementById('board');
+const chart = document.getElementById('chart');
+const slider = document.getElementById('slider');
+const playButton = document.getElementById('play');
+const speed = document.getElementById('speed');
+const cell = Math.max(4, Math.min(24, Math.floor(720 / Math.max(game.width, game.height))));
+boardCanvas.width = game.width * cell;
+boardCanvas.height = game.height * cell;
+chart.width = Math.max(boardCanvas.width, 480);
+slider.max = total;
+
+let turn = 0;
+let timer = null;
+
+function draw() {
+  const board = boardAt(turn);
+  const latest = new Set(turn > 0 ? game.moves[turn - 1].slice(1) : []);
+  const ctx = boardCanvas.getContext('2d');
+  for (let i = 0; i < cellCount; i++) {
+    const x = (i % game.width) * cell;
+    const y = Math.floor(i / game.width) * cell;
+    const owner = board[i];
+    ctx.fillStyle = owner === 0 ? game.empty : owner === 255 ? game.wall
+      : latest.has(i) ? game.latest[owner - 1] : game.colours[owner - 1];
+    ctx.fillRect(x, y, cell, cell);
+    ctx.strokeStyle = '#d0d0d0';
+    ctx.lineWidth = 0.5;
+    ctx.strokeRect(x + 0.25, y + 0.25, cell - 0.5, cell - 0.5);
+  }
+  ctx.strokeStyle = '#222';
+  ctx.lineWidth = 2;
+  latest.forEach(function (i) {
+    ctx.strokeRect((i % game.width) * cell + 1, Math.floor(i / game.width) * cell + 1, cell - 2, cell - 2);
+  });
+
+  const scores = game.scores[turn];
+  document.getElementById('scores').innerHTML = '';
+  game.players.forEach(function (name, p) {
+    const span = document.createElement('span');
+    span.style.color = game.colours[p];
+    span.textContent = name + ': ' + scores[p];
+    document.getElementById('scores').appendChild(span);
+  });
+  let status = 'Starting position';
+  if (turn > 0) {
+    const move = game.moves[turn - 1];
+    status = move.length > 1 ? 'Player ' + move[0] + ' placed a piece'
+      : 'Player ' + move[0] + ' could not move and is out';
+  }
+  if (turn === total) status += ' — ' + game.result;
+  document.getElementById('status').textContent = status;
+  document.getElementById('counter').textContent = turn + ' / ' + total;
+  slider.value = turn;
+  drawChart();
+}
+
+function drawChart() {
+  const ctx = chart.getContext('2d');
+  const w = chart.width, h = chart.height, pad = 24;
+  ctx.clearRect(0, 0, w, h);
+  let best = 1;
+  game.scores.forEach(function (s) { s.forEach(function (v) { best = Math.max(best, v); }); });
+  const px = function (t) { return pad + (w - 2 * pad) * (total === 0 ? 0 : t / total); };
+  const py = function (v) { return h - pad - (h - 2 * pad) * v / best; };
+  ctx.strokeStyle = '#ccc';
+  ctx.lineWidth = 1;
+  ctx.strokeRect(pad, pad, w - 2 * pad, h - 2 * pad);
+  ctx.fillStyle = '#666';
+  ctx.font = '11px sans-serif';
+  ctx.fillText(String(best), 2, pad + 4);
+  ctx.fillText('0', 2, h - pad);
+  game.players.forEach(function (_, p) {
+    ctx.strokeStyle = game.colours[p];
+    ctx.lineWidth = 2;
+    ctx.beginPath();
+    game.scores.forEach(function (s, t) {
+      if (t === 0) ctx.moveTo(px(t), py(s[p])); else ctx.lineTo(px(t), py(s[p]));
+    });
+    ctx.stroke();
+  });
+  ctx.strokeStyle = '#222';
+  ctx.lineWidth = 1;
+  ctx.beginPath();
+  ctx.moveTo(px(turn), pad);
+  ctx.lineTo(px(turn), h - pad);
+  ctx.stroke();
+}
+
+function go(t) {
+  turn = Math.max(0, Math.min(total, t));
+  draw();
+  if (turn === total) pause();
+}
+
+function pause() {
+  clearInterval(timer);
+  timer = null;
+  playButton.innerHTML = '&#x25B6;';
+}
+
+function play() {
+  if (turn === total) turn = 0;
+  clearInterval(timer);
+  timer = setInterval(function () { go(turn + 1); }, Number(speed.value));
+  playButton.innerHTML = '&#x23F8;';
+}
+
+playButton.onclick = function () { if (timer) pause(); else play(); };
+speed.onchange = function () { if (timer) play(); };
+slider.oninput = function () { go(Number(slider.value)); };
+document.getElementById('first').onclick = function () { go(0); };
+document.getElementById('back').onclick = function () { go(turn - 1); };
+document.getElementById('forward').onclick = function () { go(turn + 1); };
+document.getElementById('last').onclick = function () { go(total); };
+chart.onclick = function (event) {
+  const rect = chart.getBoundingClientRect();
+  const x = (event.clientX - rect.left) * chart.width / rect.width;
+  go(Math.round((x - 24) / (chart.width - 48) * total));
+};
+document.addEventListener('keydown', function (event) {
+  if (event.target.tagName === 'SELECT') return;
+  const keys = { ArrowLeft: -1, ArrowRight: 1, ArrowDown: -10, ArrowUp: 10 };
+  if (event.key in keys) go(turn + keys[event.key]);
+  else if (event.key === 'Home') go(0);
+  else if (event.key === 'End') go(total);
+  else if (event.key === ' ') { if (timer) pause(); else play(); }
+  else return;
+  event.preventDefault();
+});
+draw();
+</script>
+</body>
+</html>
+"#;
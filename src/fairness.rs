@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+use crate::{create_ai_with_options, derive_seed, play_headless_match, AIDifficulty, AIOptions, AIPlayer, Cell, GameState, SeedStream};
+
+/// Static analysis of a map: is it well-formed, and how evenly does it
+/// split the board between the players?
+#[derive(Debug, Clone)]
+pub struct MapReport {
+    pub width: usize,
+    pub height: usize,
+    pub num_players: u8,
+    pub playable_cells: usize,
+    /// `starts[p - 1]` lists player `p`'s cells at the start.
+    pub starts: Vec<Vec<(usize, usize)>>,
+    /// Shortest path between each pair of players' start areas around the
+    /// walls, or `None` if they cannot reach each other.
+    pub start_distances: Vec<(u8, u8, Option<usize>)>,
+    /// `closer_cells[p - 1]` counts the cells player `p` reaches strictly
+    /// before everyone else (the Voronoi split).
+    pub closer_cells: Vec<usize>,
+    /// Cells at the same distance from two or more players.
+    pub contested_cells: usize,
+    /// `reachable_cells[p - 1]` counts the empty cells player `p` can reach.
+    pub reachable_cells: Vec<usize>,
+    /// Reasons the map should not be used; empty if it is well-formed.
+    pub problems: Vec<String>,
+}
+
+impl MapReport {
+    pub fn is_well_formed(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Smallest share over largest share of the Voronoi split, 1.0 when even.
+    pub fn voronoi_balance(&self) -> f64 {
+        balance(&self.closer_cells)
+    }
+
+    /// Smallest over largest reachable area, 1.0 when even.
+    pub fn reachable_balance(&self) -> f64 {
+        balance(&self.reachable_cells)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Board: {}x{}, {} players, {} playable cells\n",
+            self.width, self.height, self.num_players, self.playable_cells
+        );
+        for (index, cells) in self.starts.iter().enumerate() {
+            let cells: Vec<String> = cells.iter().map(|(x, y)| format!("({} {})", y, x)).collect();
+            text.push_str(&format!("Player {} starts at {}\n", index + 1, cells.join(" ")));
+        }
+        for &(a, b, distance) in &self.start_distances {
+            match distance {
+                Some(distance) => text.push_str(&format!("Distance {}-{}: {}\n", a, b, distance)),
+                None => text.push_str(&format!("Distance {}-{}: unreachable\n", a, b)),
+            }
+        }
+        text.push_str("Closer cells:");
+        for (index, cells) in self.closer_cells.iter().enumerate() {
+            text.push_str(&format!(" P{} {}", index + 1, cells));
+        }
+        text.push_str(&format!(
+            ", contested {} (balance {:.2})\n",
+            self.contested_cells,
+            self.voronoi_balance()
+        ));
+        text.push_str("Reachable cells:");
+        for (index, cells) in self.reachable_cells.iter().enumerate() {
+            text.push_str(&format!(" P{} {}", index + 1, cells));
+        }
+        text.push_str(&format!(" (balance {:.2})\n", self.reachable_balance()));
+        if self.problems.is_empty() {
+            text.push_str("Map is well-formed\n");
+        }
+        for problem in &self.problems {
+            text.push_str(&format!("Problem: {}\n", problem));
+        }
+        text
+    }
+}
+
+fn balance(values: &[usize]) -> f64 {
+    let most = values.iter().copied().max().unwrap_or(0);
+    let least = values.iter().copied().min().unwrap_or(0);
+    if most == 0 {
+        return 1.0;
+    }
+    least as f64 / most as f64
+}
+
+/// Distance from the nearest of `sources` to every cell, stepping between
+/// edge-adjacent cells that are not walls. Unreachable cells are `None`.
+fn distances_from(game: &GameState, sources: &[(usize, usize)]) -> Vec<Vec<Option<usize>>> {
+    let mut distances = vec![vec![None; game.width]; game.height];
+    let mut queue = VecDeque::new();
+    for &(x, y) in sources {
+        distances[y][x] = Some(0);
+        queue.push_back((x, y));
+    }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let next = distances[y][x].unwrap_or(0) + 1;
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            if let Some((nx, ny)) = game.cell_offset(x, y, dx, dy) {
+                if distances[ny][nx].is_none() && game.board[ny][nx] != Cell::Blocked {
+                    distances[ny][nx] = Some(next);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
+    distances
+}
+
+/// Checks a loaded map and measures how evenly it splits the board.
+pub fn analyze_map(game: &GameState) -> MapReport {
+    let players = game.num_players;
+    let mut starts = vec![Vec::new(); players as usize];
+    for (y, row) in game.board.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if let Some(owner) = cell.owner() {
+                starts[owner as usize - 1].push((x, y));
+            }
+        }
+    }
+
+    let mut problems = Vec::new();
+    for (index, cells) in starts.iter().enumerate() {
+        match cells.len() {
+            0 => problems.push(format!("player {} has no start cell", index + 1)),
+            1 => {}
+            count => problems.push(format!("player {} has {} start cells", index + 1, count)),
+        }
+    }
+
+    let distances: Vec<_> = starts.iter().map(|cells| distances_from(game, cells)).collect();
+
+    let mut start_distances = Vec::new();
+    for (a, from_a) in distances.iter().enumerate() {
+        for (b, cells) in starts.iter().enumerate().skip(a + 1) {
+            let distance = cells.iter().filter_map(|&(x, y)| from_a[y][x]).min();
+            start_distances.push((a as u8 + 1, b as u8 + 1, distance));
+        }
+    }
+
+    let mut closer_cells = vec![0; players as usize];
+    let mut reachable_cells = vec![0; players as usize];
+    let mut contested_cells = 0;
+    for y in 0..game.height {
+        for x in 0..game.width {
+            if game.board[y][x] != Cell::Empty {
+                continue;
+            }
+            let reach: Vec<Option<usize>> = distances.iter().map(|d| d[y][x]).collect();
+            for (index, distance) in reach.iter().enumerate() {
+                if distance.is_some() {
+                    reachable_cells[index] += 1;
+                }
+            }
+            let Some(nearest) = reach.iter().flatten().min() else {
+                continue;
+            };
+            let closest: Vec<usize> = (0..reach.len()).filter(|&i| reach[i] == Some(*nearest)).collect();
+            match closest.as_slice() {
+                [only] => closer_cells[*only] += 1,
+                _ => contested_cells += 1,
+            }
+        }
+    }
+
+    for (index, &reachable) in reachable_cells.iter().enumerate() {
+        if reachable == 0 && !starts[index].is_empty() {
+            problems.push(format!("player {} is walled in", index + 1));
+        }
+    }
+
+    MapReport {
+        width: game.width,
+        height: game.height,
+        num_players: players,
+        playable_cells: game.playable_cells(),
+        starts,
+        start_distances,
+        closer_cells,
+        contested_cells,
+        reachable_cells,
+        problems,
+    }
+}
+
+/// Outcome of self-play between identical AIs, each game repeated with the
+/// start areas handed round so that seat and start position can be told
+/// apart.
+#[derive(Debug, Clone, Default)]
+pub struct SelfPlayReport {
+    pub games: u32,
+    /// `seat_wins[p - 1]`: games won by whoever moved p-th.
+    pub seat_wins: Vec<u32>,
+    /// `start_wins[p - 1]`: games won from the map's start area of player p.
+    pub start_wins: Vec<u32>,
+    pub ties: u32,
+}
+
+impl SelfPlayReport {
+    /// How much more often the first mover wins than an even share would
+    /// give, from -1.0 to 1.0.
+    pub fn first_player_advantage(&self) -> f64 {
+        if self.games == 0 || self.seat_wins.is_empty() {
+            return 0.0;
+        }
+        self.seat_wins[0] as f64 / self.games as f64 - 1.0 / self.seat_wins.len() as f64
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Self-play: {} games, {} ties\n", self.games, self.ties);
+        for (index, wins) in self.seat_wins.iter().enumerate() {
+            text.push_str(&format!("Seat {} won {}, ", index + 1, wins));
+            text.push_str(&format!("start area {} won {}\n", index + 1, self.start_wins[index]));
+        }
+        text.push_str(&format!("First-player advantage: {:+.2}\n", self.first_player_advantage()));
+        text
+    }
+}
+
+/// The map with every player's cells handed to the player `shift` seats later.
+fn rotate_starts(game: &GameState, shift: u8) -> GameState {
+    let mut rotated = game.clone();
+    let players = game.num_players;
+    for cell in rotated.board.iter_mut().flatten() {
+        if let Some(owner) = cell.owner() {
+            let new_owner = (owner - 1 + shift) % players + 1;
+            *cell = if cell.is_new() { Cell::new_for(new_owner) } else { Cell::old_for(new_owner) };
+        }
+    }
+    rotated
+}
+
+/// Plays the map with identical `difficulty` AIs for every piece seed, once
+/// per way of handing the start areas round the seats.
+pub fn self_play_fairness(game: &GameState, difficulty: &AIDifficulty, piece_seeds: &[u64]) -> SelfPlayReport {
+    let players = game.num_players;
+    let mut report = SelfPlayReport {
+        seat_wins: vec![0; players as usize],
+        start_wins: vec![0; players as usize],
+        ..SelfPlayReport::default()
+    };
+
+    for &seed in piece_seeds {
+        for shift in 0..players {
+            let map = rotate_starts(game, shift);
+            let mut ais: Vec<Box<dyn AIPlayer>> = (1..=players)
+                .map(|seat| {
+                    let options = AIOptions {
+                        seed: Some(derive_seed(seed, SeedStream::Player(seat))),
+                        ..AIOptions::default()
+                    };
+                    create_ai_with_options(difficulty.clone(), &options)
+                })
+                .collect();
+            let mut seats: Vec<&mut dyn AIPlayer> = ais.iter_mut().map(|ai| ai.as_mut() as &mut dyn AIPlayer).collect();
+            let final_state = play_headless_match(&map, &mut seats, seed);
+
+            report.games += 1;
+            match final_state.winner {
+                Some(winner) => {
+                    report.seat_wins[winner as usize - 1] += 1;
+                    // Seat `winner` started from the area of player `winner - shift`
+                    let start = (winner - 1 + players - shift) % players;
+                    report.start_wins[start as usize] += 1;
+                }
+                None => report.ties += 1,
+            }
+        }
+    }
+
+    report
+}
@@ -1,4 +1,7 @@
 use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
 use crate::piece::Piece;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -8,99 +11,406 @@ pub enum Cell {
     Player1New,    // a
     Player2Old,    // $
     Player2New,    // s
+    Player3Old,    // %
+    Player3New,    // p
+    Player4Old,    // &
+    Player4New,    // e
+    Blocked,       // #
 }
 
+/// Largest number of players a game supports.
+pub const MAX_PLAYERS: u8 = 4;
+
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Cell {
+    pub fn to_char(&self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Player1Old => '@',
+            Cell::Player1New => 'a',
+            Cell::Player2Old => '$',
+            Cell::Player2New => 's',
+            Cell::Player3Old => '%',
+            Cell::Player3New => 'p',
+            Cell::Player4Old => '&',
+            Cell::Player4New => 'e',
+            Cell::Blocked => '#',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Cell> {
+        match c {
+            '.' => Some(Cell::Empty),
+            '@' => Some(Cell::Player1Old),
+            'a' => Some(Cell::Player1New),
+            '$' => Some(Cell::Player2Old),
+            's' => Some(Cell::Player2New),
+            '%' => Some(Cell::Player3Old),
+            'p' => Some(Cell::Player3New),
+            '&' => Some(Cell::Player4Old),
+            'e' => Some(Cell::Player4New),
+            '#' => Some(Cell::Blocked),
+            _ => None,
+        }
+    }
+
+    /// The player whose territory this cell is, if any.
+    pub fn owner(&self) -> Option<u8> {
         match self {
-            Cell::Empty => write!(f, "."),
-            Cell::Player1Old => write!(f, "@"),
-            Cell::Player1New => write!(f, "a"),
-            Cell::Player2Old => write!(f, "$"),
-            Cell::Player2New => write!(f, "s"),
+            Cell::Empty | Cell::Blocked => None,
+            Cell::Player1Old | Cell::Player1New => Some(1),
+            Cell::Player2Old | Cell::Player2New => Some(2),
+            Cell::Player3Old | Cell::Player3New => Some(3),
+            Cell::Player4Old | Cell::Player4New => Some(4),
+        }
+    }
+
+    /// Whether this cell was filled by the most recent move.
+    pub fn is_new(&self) -> bool {
+        matches!(
+            self,
+            Cell::Player1New | Cell::Player2New | Cell::Player3New | Cell::Player4New
+        )
+    }
+
+    /// Territory of `player` placed before the most recent move.
+    /// Panics unless `player` is 1 to 4; file parsers refuse other seats.
+    pub fn old_for(player: u8) -> Cell {
+        match player {
+            1 => Cell::Player1Old,
+            2 => Cell::Player2Old,
+            3 => Cell::Player3Old,
+            4 => Cell::Player4Old,
+            _ => panic!("no cell for player {}", player),
+        }
+    }
+
+    /// Territory of `player` placed by the most recent move.
+    /// Panics unless `player` is 1 to 4; file parsers refuse other seats.
+    pub fn new_for(player: u8) -> Cell {
+        match player {
+            1 => Cell::Player1New,
+            2 => Cell::Player2New,
+            3 => Cell::Player3New,
+            4 => Cell::Player4New,
+            _ => panic!("no cell for player {}", player),
+        }
+    }
+}
+
+/// A move in the hand variant: which piece of the hand goes where, in
+/// which of the orientations the rules allow (0 is the piece as dealt).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandMove {
+    pub piece_index: usize,
+    pub orientation: usize,
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Which transformations players may apply to a dealt piece.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PieceRules {
+    pub rotate: bool,
+    pub mirror: bool,
+}
+
+impl PieceRules {
+    pub fn is_fixed(&self) -> bool {
+        !self.rotate && !self.mirror
+    }
+
+    /// Allowed orientation indices, as understood by `Piece::oriented`.
+    pub fn orientations(&self) -> Vec<usize> {
+        match (self.rotate, self.mirror) {
+            (false, false) => vec![0],
+            (true, false) => vec![0, 1, 2, 3],
+            (false, true) => vec![0, 4],
+            (true, true) => (0..8).collect(),
         }
     }
 }
 
+/// How the edges of the board behave.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Pieces must fit inside the board.
+    #[default]
+    Bounded,
+    /// Opposite edges are joined: a piece hanging off the right edge
+    /// continues on the left, and off the bottom continues at the top.
+    Torus,
+}
+
+/// A placement of the dealt piece in one of its allowed orientations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrientedMove {
+    pub orientation: usize,
+    pub x: usize,
+    pub y: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct GameState {
     pub board: Vec<Vec<Cell>>,
     pub width: usize,
     pub height: usize,
     pub current_player: u8,
+    /// Number of players taking turns, from 2 to `MAX_PLAYERS`.
+    pub num_players: u8,
+    /// `eliminated[p - 1]` is set once player `p` could not move. With more
+    /// than two players the others play on without them.
+    pub eliminated: Vec<bool>,
+    /// `teams[p - 1]` is player `p`'s team, numbered from 1. Teammates
+    /// score together but each still extends only their own territory.
+    /// Empty when everyone plays for themselves.
+    pub teams: Vec<u8>,
     pub turn: u32,
     pub game_over: bool,
     pub winner: Option<u8>,
+    /// The team with the most territory once a team game is over.
+    pub winning_team: Option<u8>,
+    /// Pieces dealt after the current one, visible to both players in the
+    /// preview variant. Empty in the standard game.
+    pub upcoming: Vec<Piece>,
+    /// `hands[p - 1]` holds player `p`'s pieces in the hand variant, where
+    /// players choose which piece to place. Empty in the standard game.
+    pub hands: Vec<Vec<Piece>>,
+    /// Whether pieces may be rotated or mirrored before placing them.
+    pub piece_rules: PieceRules,
+    /// Whether the board's edges wrap around.
+    pub topology: Topology,
 }
 
 impl GameState {
     pub fn new(width: usize, height: usize) -> Self {
+        Self::with_players(width, height, 2)
+    }
+
+    /// An empty board for `players` players, starting in the corners:
+    /// top-left, bottom-right, top-right, then bottom-left.
+    pub fn with_players(width: usize, height: usize, players: u8) -> Self {
+        assert!((2..=MAX_PLAYERS).contains(&players), "unsupported number of players: {}", players);
         let mut board = vec![vec![Cell::Empty; width]; height];
         
         // Set starting positions according to Filler rules
-        board[0][0] = Cell::Player1Old;
-        board[height - 1][width - 1] = Cell::Player2Old;
+        let corners = [(0, 0), (width - 1, height - 1), (width - 1, 0), (0, height - 1)];
+        for (player, &(x, y)) in (1..=players).zip(corners.iter()) {
+            board[y][x] = Cell::old_for(player);
+        }
         
         Self {
             board,
             width,
             height,
             current_player: 1,
+            num_players: players,
+            eliminated: vec![false; players as usize],
+            teams: Vec::new(),
             turn: 1,
             game_over: false,
             winner: None,
+            winning_team: None,
+            upcoming: Vec::new(),
+            hands: Vec::new(),
+            piece_rules: PieceRules::default(),
+            topology: Topology::default(),
         }
     }
 
     pub fn from_board(board: Vec<Vec<Cell>>) -> Self {
         let height = board.len();
         let width = if height > 0 { board[0].len() } else { 0 };
+        let players = board
+            .iter()
+            .flatten()
+            .filter_map(Cell::owner)
+            .max()
+            .unwrap_or(2)
+            .max(2);
         
         Self {
             board,
             width,
             height,
             current_player: 1,
+            num_players: players,
+            eliminated: vec![false; players as usize],
+            teams: Vec::new(),
             turn: 1,
             game_over: false,
             winner: None,
+            winning_team: None,
+            upcoming: Vec::new(),
+            hands: Vec::new(),
+            piece_rules: PieceRules::default(),
+            topology: Topology::default(),
         }
     }
 
+    /// Parses a map in the format used by `docker_image/maps`: one line per
+    /// row, `.` for empty cells, `@` and `$` for the players' start cells.
+    /// Maps for three or four players add `%` and `&` start cells, and may
+    /// assign teams with a line such as `teams: 1 2 1 2`. `#` marks a wall
+    /// no piece may cover; spaces and the missing ends of short rows are
+    /// walls too, so boards need not be rectangular.
+    pub fn from_map_str(text: &str) -> io::Result<Self> {
+        let mut board = Vec::new();
+        let mut teams = None;
+
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(spec) = line.strip_prefix("teams:") {
+                let parsed = parse_teams(spec)
+                    .map_err(|err| io::Error::new(err.kind(), format!("line {}: {}", line_no + 1, err)))?;
+                teams = Some(parsed);
+                continue;
+            }
+
+            let row = line
+                .chars()
+                .map(|c| {
+                    let cell = if c == ' ' { Some(Cell::Blocked) } else { Cell::from_char(c) };
+                    cell.ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("line {}: unexpected map character '{}'", line_no + 1, c),
+                        )
+                    })
+                })
+                .collect::<io::Result<Vec<Cell>>>()?;
+            board.push(row);
+        }
+
+        if board.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "map is empty"));
+        }
+        let width = board.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut board {
+            row.resize(width, Cell::Blocked);
+        }
+        // Every game has at least two players
+        let players = board.iter().flatten().filter_map(Cell::owner).max().unwrap_or(0).max(2);
+        if let Some(missing) = (1..=players).find(|&p| !board.iter().flatten().any(|c| c.owner() == Some(p))) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("map has no start cell for player {}", missing),
+            ));
+        }
+
+        let mut game = Self::from_board(board);
+        if let Some(teams) = teams {
+            game.set_teams(teams)?;
+        }
+        Ok(game)
+    }
+
+    /// Assigns `teams[p - 1]` as player `p`'s team. Teams are numbered from
+    /// 1 without gaps, and there must be at least two of them.
+    pub fn set_teams(&mut self, teams: Vec<u8>) -> io::Result<()> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        if teams.len() != self.num_players as usize {
+            return Err(invalid(format!(
+                "{} team assignments for {} players",
+                teams.len(),
+                self.num_players
+            )));
+        }
+        let count = teams.iter().copied().max().unwrap_or(0);
+        if let Some(missing) = (1..=count).find(|team| !teams.contains(team)) {
+            return Err(invalid(format!("team {} has no players", missing)));
+        }
+        if teams.contains(&0) || count < 2 {
+            return Err(invalid("teams are numbered from 1 and need at least two".to_string()));
+        }
+        self.teams = teams;
+        Ok(())
+    }
+
+    pub fn load_map<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_map_str(&text)
+    }
+
+    /// The board in the map format `from_map_str` reads, with its team
+    /// assignment if any.
+    pub fn to_map_string(&self) -> String {
+        let mut text = String::new();
+        if self.is_team_game() {
+            let teams: Vec<String> = self.teams.iter().map(|team| team.to_string()).collect();
+            text.push_str(&format!("teams: {}\n", teams.join(" ")));
+        }
+        for row in &self.board {
+            text.extend(row.iter().map(Cell::to_char));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn save_map<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_map_string())
+    }
+
+    /// The cell `(dx, dy)` away from `(x, y)`, wrapping around the edges on
+    /// a torus, or `None` if that is off a bounded board.
+    pub fn cell_offset(&self, x: usize, y: usize, dx: i32, dy: i32) -> Option<(usize, usize)> {
+        let nx = x as i64 + dx as i64;
+        let ny = y as i64 + dy as i64;
+        match self.topology {
+            Topology::Bounded => {
+                let inside = nx >= 0 && ny >= 0 && (nx as usize) < self.width && (ny as usize) < self.height;
+                inside.then_some((nx as usize, ny as usize))
+            }
+            Topology::Torus if self.width > 0 && self.height > 0 => Some((
+                nx.rem_euclid(self.width as i64) as usize,
+                ny.rem_euclid(self.height as i64) as usize,
+            )),
+            Topology::Torus => None,
+        }
+    }
+
+    /// The board cells `piece` covers at `(x, y)`, or `None` if it does not
+    /// fit on the board.
+    pub fn piece_cells(&self, piece: &Piece, x: usize, y: usize) -> Option<Vec<(usize, usize)>> {
+        // On a torus a piece longer than the board would cover itself
+        if self.topology == Topology::Torus && (piece.width > self.width || piece.height > self.height) {
+            return None;
+        }
+        piece
+            .shape
+            .iter()
+            .map(|&(px, py)| self.cell_offset(x, y, px as i32, py as i32))
+            .collect()
+    }
+
     pub fn is_valid_move(&self, piece: &Piece, x: usize, y: usize) -> bool {
         let mut overlap_count = 0;
-        let player_territory = if self.current_player == 1 {
-            [Cell::Player1Old, Cell::Player1New]
-        } else {
-            [Cell::Player2Old, Cell::Player2New]
-        };
-        
-        let opponent_territory = if self.current_player == 1 {
-            [Cell::Player2Old, Cell::Player2New]
-        } else {
-            [Cell::Player1Old, Cell::Player1New]
+
+        // Check bounds
+        let Some(cells) = self.piece_cells(piece, x, y) else {
+            return false;
         };
 
-        for (px, py) in &piece.shape {
-            let abs_x = x + px;
-            let abs_y = y + py;
-            
-            // Check bounds
-            if abs_x >= self.width || abs_y >= self.height {
-                return false;
-            }
-            
+        for (abs_x, abs_y) in cells {
             let cell = &self.board[abs_y][abs_x];
-            
-            // Check overlap with own territory
-            if player_territory.contains(cell) {
-                overlap_count += 1;
-            }
-            
-            // Check overlap with opponent territory
-            if opponent_territory.contains(cell) {
+            if *cell == Cell::Blocked {
                 return false;
             }
+            match cell.owner() {
+                // Overlap with own territory
+                Some(owner) if owner == self.current_player => overlap_count += 1,
+                // Overlap with an opponent's territory
+                Some(_) => return false,
+                None => {}
+            }
         }
         
         // Must have exactly one overlap with own territory
@@ -116,15 +426,9 @@ impl GameState {
         self.convert_new_to_old();
         
         // Place new piece
-        let new_cell = if self.current_player == 1 {
-            Cell::Player1New
-        } else {
-            Cell::Player2New
-        };
+        let new_cell = Cell::new_for(self.current_player);
 
-        for (px, py) in &piece.shape {
-            let abs_x = x + px;
-            let abs_y = y + py;
+        for (abs_x, abs_y) in self.piece_cells(piece, x, y).unwrap_or_default() {
             self.board[abs_y][abs_x] = new_cell.clone();
         }
 
@@ -134,10 +438,10 @@ impl GameState {
     fn convert_new_to_old(&mut self) {
         for row in &mut self.board {
             for cell in row {
-                match cell {
-                    Cell::Player1New => *cell = Cell::Player1Old,
-                    Cell::Player2New => *cell = Cell::Player2Old,
-                    _ => {}
+                if cell.is_new() {
+                    if let Some(owner) = cell.owner() {
+                        *cell = Cell::old_for(owner);
+                    }
                 }
             }
         }
@@ -157,49 +461,248 @@ impl GameState {
         valid_moves
     }
 
+    /// Cells that can ever be owned: the board minus its walls.
+    pub fn playable_cells(&self) -> usize {
+        self.board.iter().flatten().filter(|&cell| *cell != Cell::Blocked).count()
+    }
+
+    /// Scores of players 1 and 2. See `player_scores` for larger games.
     pub fn calculate_scores(&self) -> (u32, u32) {
-        let mut p1_score = 0;
-        let mut p2_score = 0;
+        let scores = self.player_scores();
+        (scores[0], scores[1])
+    }
+
+    /// `player_scores()[p - 1]` is the number of cells player `p` owns.
+    pub fn player_scores(&self) -> Vec<u32> {
+        let mut scores = vec![0; self.num_players.max(2) as usize];
         
         for row in &self.board {
             for cell in row {
-                match cell {
-                    Cell::Player1Old | Cell::Player1New => p1_score += 1,
-                    Cell::Player2Old | Cell::Player2New => p2_score += 1,
-                    Cell::Empty => {}
+                if let Some(owner) = cell.owner() {
+                    if let Some(score) = scores.get_mut(owner as usize - 1) {
+                        *score += 1;
+                    }
                 }
             }
         }
         
-        (p1_score, p2_score)
+        scores
+    }
+
+    pub fn is_team_game(&self) -> bool {
+        !self.teams.is_empty()
+    }
+
+    /// `player`'s team, or the player's own number outside team games.
+    pub fn team_of(&self, player: u8) -> u8 {
+        self.teams.get(player as usize - 1).copied().unwrap_or(player)
+    }
+
+    pub fn are_teammates(&self, a: u8, b: u8) -> bool {
+        self.team_of(a) == self.team_of(b)
+    }
+
+    /// `team_scores()[t - 1]` is the territory of team `t`. Outside team
+    /// games every player is their own team.
+    pub fn team_scores(&self) -> Vec<u32> {
+        let scores = self.player_scores();
+        if !self.is_team_game() {
+            return scores;
+        }
+        let mut totals = vec![0; self.teams.iter().copied().max().unwrap_or(0) as usize];
+        for (index, score) in scores.iter().enumerate() {
+            totals[self.team_of(index as u8 + 1) as usize - 1] += score;
+        }
+        totals
+    }
+
+    /// Territory of `player`'s side: their team, or themselves.
+    pub fn side_score(&self, player: u8) -> u32 {
+        self.team_scores()[self.team_of(player) as usize - 1]
+    }
+
+    /// The best score among the sides opposing `player`.
+    pub fn best_opponent_score(&self, player: u8) -> u32 {
+        let own_team = self.team_of(player) as usize;
+        self.team_scores()
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index + 1 != own_team)
+            .map(|(_, &score)| score)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_eliminated(&self, player: u8) -> bool {
+        self.eliminated.get(player as usize - 1).copied().unwrap_or(false)
+    }
+
+    /// Players still taking turns.
+    pub fn active_players(&self) -> Vec<u8> {
+        (1..=self.num_players).filter(|&p| !self.is_eliminated(p)).collect()
+    }
+
+    /// Every legal placement of `piece` in the orientations the rules
+    /// allow. Orientations with the same shape as an earlier one are
+    /// skipped. With fixed pieces this is `get_valid_moves` in orientation 0.
+    pub fn get_oriented_moves(&self, piece: &Piece) -> Vec<OrientedMove> {
+        let mut shapes: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut moves = Vec::new();
+        for orientation in self.piece_rules.orientations() {
+            let oriented = piece.oriented(orientation);
+            if shapes.contains(&oriented.shape) {
+                continue;
+            }
+            for (x, y) in self.get_valid_moves(&oriented) {
+                moves.push(OrientedMove { orientation, x, y });
+            }
+            shapes.push(oriented.shape);
+        }
+        moves
+    }
+
+    pub fn is_valid_oriented_move(&self, piece: &Piece, oriented_move: OrientedMove) -> bool {
+        self.piece_rules.orientations().contains(&oriented_move.orientation)
+            && self.is_valid_move(&piece.oriented(oriented_move.orientation), oriented_move.x, oriented_move.y)
+    }
+
+    pub fn place_oriented(&mut self, piece: &Piece, oriented_move: OrientedMove) -> bool {
+        if !self.is_valid_oriented_move(piece, oriented_move) {
+            return false;
+        }
+        self.place_piece(&piece.oriented(oriented_move.orientation), oriented_move.x, oriented_move.y)
+    }
+
+    pub fn uses_hands(&self) -> bool {
+        !self.hands.is_empty()
+    }
+
+    pub fn hand(&self, player: u8) -> &[Piece] {
+        self.hands.get(player as usize - 1).map_or(&[], |hand| hand.as_slice())
     }
 
+    /// Every legal move for the current player's hand, in every orientation
+    /// `piece_rules` allows. Shapes already reached through an earlier piece
+    /// or orientation are skipped, since they lead to the same positions.
+    pub fn get_hand_moves(&self) -> Vec<HandMove> {
+        let mut shapes: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut moves = Vec::new();
+        for (piece_index, piece) in self.hand(self.current_player).iter().enumerate() {
+            for orientation in self.piece_rules.orientations() {
+                let oriented = piece.oriented(orientation);
+                if shapes.contains(&oriented.shape) {
+                    continue;
+                }
+                for (x, y) in self.get_valid_moves(&oriented) {
+                    moves.push(HandMove { piece_index, orientation, x, y });
+                }
+                shapes.push(oriented.shape);
+            }
+        }
+        moves
+    }
+
+    pub fn is_valid_hand_move(&self, hand_move: HandMove) -> bool {
+        self.piece_rules.orientations().contains(&hand_move.orientation)
+            && self
+                .hand(self.current_player)
+                .get(hand_move.piece_index)
+                .is_some_and(|piece| self.is_valid_move(&piece.oriented(hand_move.orientation), hand_move.x, hand_move.y))
+    }
+
+    /// Places a piece from the current player's hand and removes it from the
+    /// hand. Returns the piece as placed, or `None` if the move is invalid;
+    /// the caller refills the hand.
+    pub fn play_hand_move(&mut self, hand_move: HandMove) -> Option<Piece> {
+        if !self.is_valid_hand_move(hand_move) {
+            return None;
+        }
+        let hand = &mut self.hands[self.current_player as usize - 1];
+        let piece = hand.remove(hand_move.piece_index).oriented(hand_move.orientation);
+        self.place_piece(&piece, hand_move.x, hand_move.y);
+        Some(piece)
+    }
+
+    /// The piece dealt after the current one: the head of the preview
+    /// queue, or `fallback` when nothing is previewed.
+    pub fn next_piece<'a>(&'a self, fallback: &'a Piece) -> &'a Piece {
+        self.upcoming.first().unwrap_or(fallback)
+    }
+
+    /// Drops the head of the preview queue, as happens when it is dealt.
+    pub fn pop_preview(&mut self) {
+        if !self.upcoming.is_empty() {
+            self.upcoming.remove(0);
+        }
+    }
+
+    /// Passes the turn to the next player still in the game.
     pub fn switch_player(&mut self) {
-        self.current_player = if self.current_player == 1 { 2 } else { 1 };
+        let players = self.num_players.max(2);
+        let mut next = self.current_player;
+        for _ in 0..players {
+            next = next % players + 1;
+            if !self.is_eliminated(next) {
+                break;
+            }
+        }
+        self.current_player = next;
         self.turn += 1;
     }
 
+    /// Checks whether the current player can place `piece`. If not, they
+    /// are out: the game ends once at most one player is left, otherwise the
+    /// turn passes on and the next player is checked with the same piece.
     pub fn check_game_over(&mut self, piece: &Piece) -> bool {
-        let valid_moves = self.get_valid_moves(piece);
-        
-        if valid_moves.is_empty() {
-            self.game_over = true;
-            let (p1_score, p2_score) = self.calculate_scores();
-            
-            if p1_score > p2_score {
-                self.winner = Some(1);
-            } else if p2_score > p1_score {
-                self.winner = Some(2);
-            } else {
-                self.winner = None; // Tie
+        while self.get_oriented_moves(piece).is_empty() {
+            if self.eliminate_current() {
+                return true;
             }
-            
-            return true;
         }
         
         false
     }
 
+    /// Removes the current player from the game because they cannot move.
+    /// Returns true if that ended the game, which happens once the players
+    /// left all belong to one side.
+    pub fn eliminate_current(&mut self) -> bool {
+        if let Some(out) = self.eliminated.get_mut(self.current_player as usize - 1) {
+            *out = true;
+        }
+        let active = self.active_players();
+        if active.iter().all(|&player| self.are_teammates(player, active[0])) {
+            self.end_game();
+            return true;
+        }
+        self.switch_player();
+        false
+    }
+
+    /// Ends the game and decides the winner by territory: the player with
+    /// the most cells, or no one if the best score is shared. Team games
+    /// also record the team with the most cells in `winning_team`.
+    pub fn end_game(&mut self) {
+        self.game_over = true;
+        self.winner = unique_leader(&self.player_scores()); // None on a tie
+        self.winning_team = if self.is_team_game() {
+            unique_leader(&self.team_scores())
+        } else {
+            None
+        };
+    }
+
+    /// "Player N wins", "Team N wins" in team games, or "Tie", as decided
+    /// by `end_game`.
+    pub fn result_text(&self) -> String {
+        let winner = if self.is_team_game() {
+            self.winning_team.map(|team| format!("Team {}", team))
+        } else {
+            self.winner.map(|player| format!("Player {}", player))
+        };
+        winner.map_or_else(|| "Tie".to_string(), |winner| format!("{} wins", winner))
+    }
+
     pub fn display_board(&self) -> String {
         let mut result = String::new();
         
@@ -222,3 +725,25 @@ impl GameState {
         result
     }
 }
+
+/// The 1-based index of the single highest score, if no other matches it.
+pub fn unique_leader(scores: &[u32]) -> Option<u8> {
+    let best = scores.iter().copied().max()?;
+    let mut leaders = (0..scores.len()).filter(|&i| scores[i] == best);
+    match (leaders.next(), leaders.next()) {
+        (Some(leader), None) => Some(leader as u8 + 1),
+        _ => None,
+    }
+}
+
+/// Parses a team list such as `1 2 1 2` or `1,2,1,2`.
+pub fn parse_teams(spec: &str) -> io::Result<Vec<u8>> {
+    spec.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.parse().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("invalid team number '{}'", part))
+            })
+        })
+        .collect()
+}
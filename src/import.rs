@@ -0,0 +1,221 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::{GameState, Piece, Replay};
+
+/// Reads a captured log of the official game engine into a replay.
+pub fn load_engine_log<P: AsRef<Path>>(path: P) -> io::Result<Replay> {
+    let text = fs::read_to_string(path)?;
+    import_engine_log(&text)
+}
+
+/// Converts the output of the official game engine into a replay.
+///
+/// The log is read as the engine prints it: `$$$ exec pN : [command]` lines
+/// name the seats, every turn shows the board (`Anfield` or `Plateau`, rows
+/// prefixed with their number), the piece (`Piece`, filled cells `O` or `*`)
+/// and the answer, `-> Answer (@): x y` or `<got (O): [a, b]>`. Players may be
+/// written `@`/`$` or `O`/`X`. Each board is checked against the moves read
+/// so far. When both orders of an answer's coordinates are legal, the next
+/// board decides which was meant. An answer that cannot be placed is
+/// recorded as the player dropping out.
+pub fn import_engine_log(text: &str) -> io::Result<Replay> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut replay: Option<Replay> = None;
+    let mut game: Option<GameState> = None;
+    let mut piece: Option<Piece> = None;
+    let mut seats: Vec<(usize, String)> = Vec::new();
+    let mut seed = 0;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let line_no = i + 1;
+
+        if is_board_header(line) {
+            let (snapshot, next) = read_board(&lines, i)?;
+            match &game {
+                Some(game) if !same_territory(game, &snapshot) => {
+                    return Err(invalid(format!("line {}: board does not match the moves before it", line_no)));
+                }
+                Some(_) => {}
+                None => {
+                    replay = Some(Replay::new(&snapshot, 0, Vec::new()));
+                    game = Some(snapshot);
+                }
+            }
+            i = next;
+            continue;
+        }
+
+        if line.starts_with("Piece ") {
+            let (read, next) = read_piece(&lines, i)?;
+            piece = Some(read);
+            i = next;
+            continue;
+        }
+
+        if let Some((player, a, b)) = parse_answer(line) {
+            let (Some(game), Some(replay), Some(piece)) = (game.as_mut(), replay.as_mut(), piece.take()) else {
+                return Err(invalid(format!("line {}: answer before a board and a piece", line_no)));
+            };
+            if player > game.num_players {
+                return Err(invalid(format!("line {}: no player {} on the board", line_no, player)));
+            }
+            game.current_player = player;
+
+            let mut candidates = vec![(a, b), (b, a)];
+            candidates.dedup();
+            candidates.retain(|&(x, y)| game.is_valid_move(&piece, x, y));
+            if candidates.len() > 1 {
+                // Both orders fit: keep the one the engine's next board shows
+                if let Some(next_board) = find_board(&lines, i + 1) {
+                    let (snapshot, _) = read_board(&lines, next_board)?;
+                    candidates.retain(|&(x, y)| {
+                        let mut placed = game.clone();
+                        placed.place_piece(&piece, x, y);
+                        same_territory(&placed, &snapshot)
+                    });
+                }
+            }
+
+            match candidates.first() {
+                Some(&(x, y)) => {
+                    game.place_piece(&piece, x, y);
+                    replay.record_move(player, &piece, x, y);
+                }
+                None if !game.is_eliminated(player) => {
+                    let eliminated_before = game.eliminated.clone();
+                    game.eliminated[player as usize - 1] = true;
+                    replay.record_dropouts(&eliminated_before, game, &piece);
+                }
+                None => {}
+            }
+            i += 1;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("$$$ exec p") {
+            if let Some((seat, command)) = rest.split_once(':') {
+                let command = command.trim().trim_start_matches('[').trim_end_matches(']').trim();
+                if let Ok(seat) = seat.trim().parse() {
+                    seats.push((seat, format!("bot {}", command)));
+                }
+            }
+        } else if let Some(value) = line.strip_prefix("seed:") {
+            seed = value.trim().parse().unwrap_or(0);
+        }
+        i += 1;
+    }
+
+    let mut replay = replay.ok_or_else(|| invalid("no Anfield or Plateau board in the log".to_string()))?;
+    replay.seed = seed;
+    // Seats the board has no start cell for took no part
+    seats.retain(|(seat, _)| (1..=replay.initial.num_players as usize).contains(seat));
+    seats.sort_by_key(|(seat, _)| *seat);
+    replay.players = seats.into_iter().map(|(_, command)| command).collect();
+    Ok(replay)
+}
+
+fn is_board_header(line: &str) -> bool {
+    line.starts_with("Anfield ") || line.starts_with("Plateau ")
+}
+
+fn find_board(lines: &[&str], from: usize) -> Option<usize> {
+    (from..lines.len()).find(|&index| is_board_header(lines[index].trim()))
+}
+
+/// A board row as the engine prints it, `007 ..@@..`, without its number.
+fn board_row(line: &str) -> Option<&str> {
+    let (number, row) = line.trim_end().split_once(' ')?;
+    (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) && !row.is_empty()).then_some(row)
+}
+
+/// Reads the board starting at the header line `start`. Returns it and the
+/// index of the first line after it.
+fn read_board(lines: &[&str], start: usize) -> io::Result<(GameState, usize)> {
+    let mut i = start + 1;
+    // Column numbers above the rows
+    if lines.get(i).is_some_and(|line| board_row(line).is_none() && line.starts_with(' ')) {
+        i += 1;
+    }
+    let mut rows = Vec::new();
+    while let Some(row) = lines.get(i).and_then(|line| board_row(line)) {
+        let row: String = row
+            .chars()
+            .map(|c| match c {
+                'O' => '@',
+                'o' => 'a',
+                'X' => '$',
+                'x' => 's',
+                other => other,
+            })
+            .collect();
+        rows.push(row);
+        i += 1;
+    }
+    if rows.is_empty() {
+        return Err(invalid(format!("line {}: board has no rows", start + 1)));
+    }
+    let game = GameState::from_map_str(&rows.join("\n"))
+        .map_err(|err| invalid(format!("board at line {}: {}", start + 1, err)))?;
+    Ok((game, i))
+}
+
+/// Reads the piece starting at the header line `start`. Returns it and the
+/// index of the first line after it.
+fn read_piece(lines: &[&str], start: usize) -> io::Result<(Piece, usize)> {
+    let mut shape = Vec::new();
+    let mut i = start + 1;
+    while let Some(line) = lines.get(i).map(|line| line.trim_end()) {
+        if line.is_empty() || !line.chars().all(|c| matches!(c, '.' | '*' | 'O')) {
+            break;
+        }
+        let y = i - start - 1;
+        shape.extend(line.chars().enumerate().filter(|&(_, c)| c != '.').map(|(x, _)| (x, y)));
+        i += 1;
+    }
+    if shape.is_empty() {
+        return Err(invalid(format!("line {}: piece has no cells", start + 1)));
+    }
+    Ok((Piece::new(shape), i))
+}
+
+/// The player and the two numbers of an answer line, in the order printed.
+fn parse_answer(line: &str) -> Option<(u8, usize, usize)> {
+    let (symbol, numbers) = if let Some(rest) = line.strip_prefix("-> Answer (") {
+        let (symbol, numbers) = rest.split_once("):")?;
+        (symbol, numbers.to_string())
+    } else {
+        let rest = line.strip_prefix("<got (")?;
+        let (symbol, numbers) = rest.split_once("):")?;
+        let numbers = numbers.trim().strip_prefix('[')?.strip_suffix('>')?.trim_end().strip_suffix(']')?;
+        (symbol, numbers.replace(',', " "))
+    };
+    let player = match symbol.trim() {
+        "@" | "a" | "O" | "o" => 1,
+        "$" | "s" | "X" | "x" => 2,
+        _ => return None,
+    };
+    let numbers: Vec<usize> = numbers.split_whitespace().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [a, b] => Some((player, *a, *b)),
+        _ => None,
+    }
+}
+
+/// Whether both boards give every cell the same owner, ignoring which
+/// cells were placed last.
+fn same_territory(a: &GameState, b: &GameState) -> bool {
+    a.width == b.width
+        && a.height == b.height
+        && a.board
+            .iter()
+            .flatten()
+            .zip(b.board.iter().flatten())
+            .all(|(x, y)| x.owner() == y.owner() && (x.owner().is_some() || x == y))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
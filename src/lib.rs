@@ -4,6 +4,16 @@ pub mod ai;
 pub mod player;
 pub mod visualizer;
 pub mod utils;
+pub mod tuner;
+pub mod weights;
+pub mod opponent;
+pub mod seed;
+pub mod mapgen;
+pub mod fairness;
+pub mod save;
+pub mod replay;
+pub mod import;
+pub mod export;
 
 pub use game::*;
 pub use piece::*;
@@ -11,6 +21,16 @@ pub use ai::*;
 pub use player::*;
 pub use visualizer::*;
 pub use utils::*;
+pub use tuner::*;
+pub use weights::*;
+pub use opponent::*;
+pub use seed::*;
+pub use mapgen::*;
+pub use fairness::*;
+pub use save::*;
+pub use replay::*;
+pub use import::*;
+pub use export::*;
 
 #[cfg(test)]
 mod tests {
@@ -127,7 +147,7 @@ mod tests {
 
     #[test]
     fn test_valid_move_detection_2() {
-        let mut game = GameState::new(5, 5);
+        let game = GameState::new(5, 5);
         let piece = Piece::new(vec![(0, 0), (1, 0)]); // 2x1 horizontal piece
         
         // Valid move: overlaps with player 1's starting position
@@ -271,4 +291,989 @@ mod tests {
         assert_eq!(game.board[0][0], Cell::Player1Old);
         assert_eq!(game.board[2][2], Cell::Player2Old);
     }
+
+    #[test]
+    fn test_weights_config_parsing() {
+        let text = "# tuned weights\nscore_diff = 120\n\n[20x15]\ncompactness = 3\n";
+        let config = WeightsConfig::parse(text).unwrap();
+
+        assert_eq!(config.default.score_diff, 120);
+        assert_eq!(config.default.compactness, 1);
+
+        let large = config.for_board(20, 15);
+        assert_eq!(large.score_diff, 120);
+        assert_eq!(large.compactness, 3);
+        assert_eq!(config.for_board(10, 10), &config.default);
+
+        // Round trip through the config format
+        let reparsed = WeightsConfig::parse(&config.to_config_string()).unwrap();
+        assert_eq!(reparsed, config);
+
+        assert!(WeightsConfig::parse("unknown = 1").is_err());
+        assert!(WeightsConfig::parse("[big]\nscore_diff = 1").is_err());
+    }
+
+    #[test]
+    fn test_weight_override() {
+        let mut weights = EvalWeights::default();
+        weights.apply_override("blocking=7").unwrap();
+        assert_eq!(weights.blocking, 7);
+        assert!(weights.apply_override("blocking").is_err());
+        assert!(weights.apply_override("nope=1").is_err());
+    }
+
+    #[test]
+    fn test_map_loading() {
+        let game = GameState::from_map_str("....\n.@..\n..$.\n").unwrap();
+        assert_eq!(game.width, 4);
+        assert_eq!(game.height, 3);
+        assert_eq!(game.board[1][1], Cell::Player1Old);
+        assert_eq!(game.board[2][2], Cell::Player2Old);
+
+        assert_eq!(GameState::from_map_str("@.\n.$.\n").unwrap().board[0][2], Cell::Blocked);
+        assert!(GameState::from_map_str(".x.\n").is_err());
+    }
+
+    #[test]
+    fn test_headless_game_is_deterministic() {
+        let initial = GameState::new(10, 8);
+        let play = || {
+            let mut ai1 = create_ai(AIDifficulty::Medium);
+            let mut ai2 = create_ai(AIDifficulty::Medium);
+            play_headless_game(&initial, ai1.as_mut(), ai2.as_mut(), 7)
+        };
+
+        let first = play();
+        let second = play();
+        assert!(first.game_over);
+        assert_eq!(first.board, second.board);
+        assert_eq!(first.winner, second.winner);
+    }
+
+    #[test]
+    fn test_tuner_generation() {
+        // Easy reads no weights, so there is nothing to tune
+        assert!(TunerConfig::new(AIDifficulty::Easy, vec![GameState::new(8, 8)]).is_err());
+        let mut config = TunerConfig::new(AIDifficulty::Medium, vec![GameState::new(8, 8)]).unwrap();
+        config.piece_seeds = vec![1, 2];
+        config.generations = 2;
+
+        let mut tuner = Tuner::new(config, EvalWeights::default());
+        let mut log = Vec::new();
+        let tuned = tuner.run(&mut log).unwrap();
+
+        assert_eq!(tuner.history().len(), 2);
+        assert_eq!(String::from_utf8(log).unwrap().lines().count(), 2);
+        // Only the Greedy weights are tuned for Medium
+        assert_eq!(tuned.score_diff, EvalWeights::default().score_diff);
+        let result = tuner.history()[0].result;
+        assert_eq!(result.games(), 4);
+    }
+
+    #[test]
+    fn test_parallel_minimax_matches_single_thread() {
+        let mut game = GameState::new(12, 10);
+        let mut generator = PieceGenerator::new(3);
+        let mut greedy = GreedyAI::new();
+        for _ in 0..6 {
+            let piece = generator.next();
+            if let Some((x, y)) = greedy.choose_move(&game, &piece) {
+                game.place_piece(&piece, x, y);
+            }
+            game.switch_player();
+        }
+
+        let piece = Piece::new(vec![(0, 0), (1, 0), (1, 1)]);
+        let mut single = MinimaxAI::new(3);
+        let mut parallel = MinimaxAI::new(3).with_threads(4);
+
+        let expected = single.choose_move(&game, &piece);
+        assert!(expected.is_some());
+        assert_eq!(parallel.choose_move(&game, &piece), expected);
+    }
+
+    #[test]
+    fn test_minimax_time_limit() {
+        // Far too deep to finish: the search has to give up inside a root move
+        let mut game = GameState::new(40, 30);
+        let piece = Piece::new(vec![(0, 0), (1, 0), (1, 1)]);
+        for _ in 0..6 {
+            let (x, y) = game.get_valid_moves(&piece)[0];
+            game.place_piece(&piece, x, y);
+            game.switch_player();
+        }
+        let limit = std::time::Duration::from_millis(100);
+        let mut ai = MinimaxAI::new(12).with_time_limit(limit);
+
+        let start = std::time::Instant::now();
+        let (x, y) = ai.choose_move(&game, &piece).unwrap();
+        assert!(start.elapsed() < limit * 5, "took {:?}", start.elapsed());
+        assert!(game.is_valid_move(&piece, x, y));
+
+        // Nothing finishes in no time; the one-ply fallback still moves
+        let mut ai = MinimaxAI::new(12).with_time_limit(std::time::Duration::ZERO);
+        let (x, y) = ai.choose_move(&game, &piece).unwrap();
+        assert!(game.is_valid_move(&piece, x, y));
+
+        // The analysis reports the depth the scores really come from
+        let analysis = MinimaxAI::new(12).with_time_limit(limit).analyze(&game, &piece);
+        assert_eq!(analysis.depth, 1);
+        assert_eq!(analysis.move_scores.len() + analysis.unsearched.len(), game.get_valid_moves(&piece).len());
+    }
+
+    #[test]
+    fn test_search_analysis() {
+        let game = GameState::new(8, 8);
+        let piece = Piece::new(vec![(0, 0), (1, 0)]);
+        let valid_moves = game.get_valid_moves(&piece);
+
+        let mut minimax = MinimaxAI::new(3);
+        let analysis = minimax.analyze(&game, &piece);
+        assert_eq!(analysis.best_move, minimax.choose_move(&game, &piece));
+        assert_eq!(analysis.move_scores.len(), valid_moves.len());
+        assert!(analysis.unsearched.is_empty());
+        assert_eq!(analysis.principal_variation.first().copied(), analysis.best_move);
+        assert_eq!(analysis.principal_variation.len(), 3);
+        assert_eq!(analysis.depth, 3);
+        assert!(analysis.nodes > valid_moves.len() as u64);
+        assert!(analysis.move_scores.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        let mut greedy = GreedyAI::new();
+        let analysis = greedy.analyze(&game, &piece);
+        assert_eq!(analysis.best_move, greedy.choose_move(&game, &piece));
+        assert_eq!(analysis.move_scores.len(), valid_moves.len());
+        assert!(analysis.report(3).contains("Best move"));
+    }
+
+    #[test]
+    fn test_pondering_warms_table_without_changing_moves() {
+        let piece = Piece::new(vec![(0, 0), (1, 0), (1, 1)]);
+        let mut game = GameState::new(10, 10);
+        let mut generator = PieceGenerator::new(5);
+        let mut greedy = GreedyAI::new();
+        for _ in 0..4 {
+            let opening = generator.next();
+            if let Some((x, y)) = greedy.choose_move(&game, &opening) {
+                game.place_piece(&opening, x, y);
+            }
+            game.switch_player();
+        }
+        let mut pondering = MinimaxAI::new(3).with_pondering(true);
+        let mut plain = MinimaxAI::new(3);
+
+        let (x, y) = pondering.choose_move(&game, &piece).unwrap();
+        game.place_piece(&piece, x, y);
+        game.switch_player();
+
+        let table_before = pondering.table_size();
+        pondering.start_pondering(&game, &piece);
+        let started = std::time::Instant::now();
+        while pondering.is_pondering() && started.elapsed() < std::time::Duration::from_secs(10) {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(!pondering.is_pondering());
+        assert!(pondering.table_size() > table_before);
+
+        let reply = game.get_valid_moves(&piece)[0];
+        game.place_piece(&piece, reply.0, reply.1);
+        game.switch_player();
+        assert_eq!(pondering.choose_move(&game, &piece), plain.choose_move(&game, &piece));
+    }
+
+    #[test]
+    fn test_pondering_cancellation() {
+        let piece = Piece::new(vec![(0, 0), (0, 1)]);
+        let mut game = GameState::new(12, 12);
+        game.switch_player();
+        let mut ai = MinimaxAI::new(4).with_pondering(true);
+
+        // A new position arrives immediately; the background search must stop
+        ai.start_pondering(&game, &piece);
+        game.switch_player();
+        let result = ai.choose_move(&game, &piece);
+        assert!(!ai.is_pondering());
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_infer_placement() {
+        let piece = Piece::new(vec![(0, 0), (1, 0)]);
+        let mut game = GameState::new(10, 10);
+        let before = game.clone();
+        let (x, y) = game.get_valid_moves(&piece)[0];
+        game.place_piece(&piece, x, y);
+
+        let cells = infer_placement(&before, &game, 1);
+        assert_eq!(cells.len(), 1);
+        assert!(infer_placement(&before, &game, 2).is_empty());
+
+        // Touching a teammate is not contact; touching an opponent is
+        let mut team_game = GameState::from_map_str("@%.\n...\n&.$\nteams: 1 2 1 2\n").unwrap();
+        assert_eq!(MoveFeatures::of_cells(&team_game, &[(0, 1)], 1).contact, 1.0);
+        team_game.board[2][0] = Cell::Empty;
+        assert_eq!(MoveFeatures::of_cells(&team_game, &[(0, 1)], 1).contact, 0.0);
+    }
+
+    #[test]
+    fn test_opponent_model_detects_center_seeking() {
+        let mut game = GameState::new(16, 16);
+        let mut generator = PieceGenerator::new(3);
+        let mut greedy = GreedyAI::new();
+        let mut model = OpponentModel::new(2);
+
+        for _ in 0..12 {
+            let piece = generator.next();
+            if let Some((x, y)) = greedy.choose_move(&game, &piece) {
+                game.place_piece(&piece, x, y);
+            }
+            game.switch_player();
+
+            // Player 2 always takes the placement closest to the center
+            let piece = generator.next();
+            let best = game.get_valid_moves(&piece).into_iter().max_by(|a, b| {
+                let a = MoveFeatures::of_move(&game, &piece, a.0, a.1, 2).centrality;
+                let b = MoveFeatures::of_move(&game, &piece, b.0, b.1, 2).centrality;
+                a.partial_cmp(&b).unwrap()
+            });
+            let before = game.clone();
+            if let Some((x, y)) = best {
+                game.place_piece(&piece, x, y);
+                model.observe(&before, &game, Some(&piece));
+            }
+            game.switch_player();
+        }
+
+        assert!(model.is_confident());
+        assert_eq!(model.style(), OpponentStyle::CenterSeeking);
+    }
+
+    #[test]
+    fn test_minimax_with_opponent_model() {
+        let piece = Piece::new(vec![(0, 0), (1, 0), (1, 1)]);
+        let mut game = GameState::new(10, 10);
+        let mut ai = MinimaxAI::new(3).with_opponent_model(true).with_opponent_replies(2);
+        let mut opponent = GreedyAI::new();
+
+        for _ in 0..4 {
+            let (x, y) = ai.choose_move(&game, &piece).unwrap();
+            game.place_piece(&piece, x, y);
+            game.switch_player();
+
+            let before = game.clone();
+            let Some((x, y)) = opponent.choose_move(&game, &piece) else {
+                break;
+            };
+            game.place_piece(&piece, x, y);
+            ai.observe_opponent_move(&before, &game, Some(&piece));
+            game.switch_player();
+        }
+
+        assert!(ai.opponent_model().unwrap().observations() > 0);
+        assert!(ai.opponent_style().is_some());
+        if let Some((x, y)) = ai.choose_move(&game, &piece) {
+            assert!(game.is_valid_move(&piece, x, y));
+        }
+    }
+
+    #[test]
+    fn test_seeded_random_ai_is_reproducible() {
+        let piece = Piece::new(vec![(0, 0)]);
+        let mut game = GameState::new(12, 12);
+        for _ in 0..6 {
+            let (x, y) = game.get_valid_moves(&piece)[0];
+            game.place_piece(&piece, x, y);
+        }
+
+        let mut first = RandomAI::with_seed(7);
+        let mut second = RandomAI::with_seed(7);
+        let moves: Vec<_> = (0..10).map(|_| first.choose_move(&game, &piece)).collect();
+        let replayed: Vec<_> = (0..10).map(|_| second.choose_move(&game, &piece)).collect();
+        assert_eq!(moves, replayed);
+
+        assert_ne!(derive_seed(7, SeedStream::Player(1)), derive_seed(7, SeedStream::Player(2)));
+        assert_ne!(derive_seed(7, SeedStream::Pieces), derive_seed(8, SeedStream::Pieces));
+    }
+
+    #[test]
+    fn test_master_seed_replays_random_game() {
+        let map = GameState::new(14, 10);
+        let play = |master: u64| {
+            let options = |player: u8| AIOptions {
+                seed: Some(derive_seed(master, SeedStream::Player(player))),
+                ..AIOptions::default()
+            };
+            let mut p1 = create_ai_with_options(AIDifficulty::Easy, &options(1));
+            let mut p2 = create_ai_with_options(AIDifficulty::Easy, &options(2));
+            let piece_seed = derive_seed(master, SeedStream::Pieces);
+            play_headless_game(&map, p1.as_mut(), p2.as_mut(), piece_seed).board
+        };
+
+        assert_eq!(play(99), play(99));
+    }
+
+    #[test]
+    fn test_piece_set_parsing() {
+        let text = "# two pieces\nweight = 3\n**\n*.\n\n.*\n**\n";
+        let set = PieceSet::parse(text).unwrap();
+        let PieceSet::Weighted(pieces) = &set else {
+            panic!("expected weighted set");
+        };
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].1, 3);
+        assert_eq!(pieces[0].0.shape, vec![(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(pieces[1].1, 1);
+
+        assert!(PieceSet::parse("").is_err());
+        assert!(PieceSet::parse("*x*\n").is_err());
+        assert!(PieceSet::parse("**\nweight = 2\n").is_err());
+        // Drawing needs the total weight, so it must fit
+        assert!(PieceSet::parse("weight = 4000000000\n*\n\nweight = 4000000000\n**\n").is_err());
+
+        // Only the weighted piece can come out of a set with one zero weight
+        let set = PieceSet::parse("weight = 0\n*\n\n**\n").unwrap();
+        let mut generator = PieceGenerator::with_set(1, set);
+        assert!((0..20).all(|_| generator.next().shape.len() == 2));
+
+        // Pondering prepares for the heaviest pieces; a zero weight never comes
+        let set = PieceSet::parse("weight = 0\n*\n\n**\n\nweight = 5\n*\n*\n").unwrap();
+        let likely: Vec<_> = set.likely_pieces(3).iter().map(|piece| piece.shape.clone()).collect();
+        assert_eq!(likely, vec![vec![(0, 0), (0, 1)], vec![(0, 0), (1, 0)]]);
+        assert!(PieceSet::official_like().likely_pieces(3).iter().all(|piece| piece.shape.len() <= 2));
+    }
+
+    #[test]
+    fn test_piece_presets() {
+        for name in PieceSet::PRESET_NAMES {
+            assert!(PieceSet::preset(name).is_some());
+        }
+        assert!(PieceSet::preset("unknown").is_none());
+
+        let mut small = PieceGenerator::with_set(3, PieceSet::small_only());
+        assert!((0..50).all(|_| small.next().shape.len() <= 3));
+
+        let mut official = PieceGenerator::with_set(3, PieceSet::official_like());
+        for _ in 0..50 {
+            let piece = official.next();
+            assert!(!piece.shape.is_empty() && piece.shape.len() <= 6);
+            // Every cell touches another one, so the shape is one piece
+            assert!(piece.shape.len() == 1
+                || piece.shape.iter().all(|&(x, y)| piece.shape.iter().any(|&(ox, oy)| x.abs_diff(ox) + y.abs_diff(oy) == 1)));
+            assert!(piece.shape.iter().any(|&(x, _)| x == 0) && piece.shape.iter().any(|&(_, y)| y == 0));
+        }
+    }
+
+    #[test]
+    fn test_polyomino_transforms() {
+        let l_piece = Piece::new(vec![(0, 0), (0, 1), (0, 2), (1, 2)]);
+        let rotated = l_piece.rotated();
+        assert_eq!((rotated.width, rotated.height), (3, 2));
+        assert_eq!(rotated.rotated().rotated().rotated().shape, l_piece.normalized().shape);
+        assert_eq!(l_piece.mirrored().mirrored().shape, l_piece.normalized().shape);
+        assert_eq!(l_piece.variants().len(), 8);
+        assert_eq!(Piece::new(vec![(0, 0), (1, 0), (0, 1), (1, 1)]).variants().len(), 1);
+        assert_eq!(Piece::new(vec![(0, 0), (1, 0)]).variants().len(), 2);
+
+        let shifted = Piece::new(vec![(2, 1), (2, 2), (2, 3), (3, 3)]);
+        assert!(shifted.same_fixed_shape(&l_piece));
+        assert!(!l_piece.same_fixed_shape(&l_piece.mirrored()));
+        assert!(l_piece.same_free_shape(&l_piece.mirrored().rotated()));
+
+        assert!(l_piece.is_connected());
+        assert!(!Piece::new(vec![(0, 0), (1, 1)]).is_connected());
+    }
+
+    #[test]
+    fn test_polyomino_enumeration() {
+        let counts: Vec<usize> = (1..=6).map(|n| Piece::fixed_polyominoes(n).len()).collect();
+        assert_eq!(counts, vec![1, 2, 6, 19, 63, 216]);
+
+        let tetrominoes = Piece::fixed_polyominoes(4);
+        let mut free: Vec<_> = tetrominoes.iter().map(|piece| piece.free_form()).collect();
+        free.sort();
+        free.dedup();
+        assert_eq!(free.len(), 5);
+        assert!(tetrominoes.iter().all(|piece| piece.is_connected()));
+    }
+
+    #[test]
+    fn test_piece_set_validation() {
+        let set = PieceSet::load("pieces/pentominoes.txt").unwrap();
+        let PieceSet::Weighted(pieces) = &set else {
+            panic!("expected weighted set");
+        };
+        let mut pentominoes: Vec<_> = pieces
+            .iter()
+            .filter(|(piece, _)| piece.shape.len() == 5)
+            .map(|(piece, _)| piece.free_form())
+            .collect();
+        pentominoes.sort();
+        pentominoes.dedup();
+        assert_eq!(pentominoes.len(), 12);
+
+        let error = PieceSet::parse("**\n\n*.\n.*\n").unwrap_err();
+        assert!(error.to_string().contains("line 3"));
+        assert!(PieceSet::parse("**\n\n**\n").is_err());
+        assert!(PieceSet::parse("weight = 0\n*\n").is_err());
+        assert!(PieceSet::parse("...\n").is_err());
+
+        // Rotations are distinct pieces when pieces cannot be rotated
+        assert!(PieceSet::parse("**\n\n*\n*\n").is_ok());
+        assert!(PieceSet::classic().validate().is_ok());
+        assert!(PieceSet::RandomPolyominoes { max_cells: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_piece_preview() {
+        let mut peeking = PieceGenerator::new(11);
+        let mut plain = PieceGenerator::new(11);
+        let preview = peeking.preview(3);
+        assert_eq!(preview.len(), 3);
+        for expected in &preview {
+            assert_eq!(peeking.next().shape, expected.shape);
+            assert_eq!(plain.next().shape, expected.shape);
+        }
+        for _ in 0..5 {
+            peeking.preview(2);
+            assert_eq!(peeking.next().shape, plain.next().shape);
+        }
+
+        // Knowing the opponent gets a large piece changes the evaluation
+        let dot = Piece::new(vec![(0, 0)]);
+        let rectangle = Piece::new(vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+        let mut game = GameState::new(8, 8);
+        let mut ai = MinimaxAI::new(2);
+        let guessed = ai.analyze(&game, &dot);
+        game.upcoming = vec![rectangle];
+        let known = ai.analyze(&game, &dot);
+        assert!(known.move_scores[0].1 < guessed.move_scores[0].1);
+
+        game.place_piece(&dot, 1, 0);
+        game.switch_player();
+        game.pop_preview();
+        assert!(game.upcoming.is_empty());
+        assert_eq!(game.next_piece(&dot).shape, dot.shape);
+    }
+
+    #[test]
+    fn test_hand_variant() {
+        let dot = Piece::new(vec![(0, 0)]);
+        let bar = Piece::new(vec![(0, 0), (1, 0), (2, 0)]);
+        let mut game = GameState::new(8, 8);
+        game.hands = vec![vec![dot.clone(), bar.clone(), dot.clone()], vec![bar.clone(), dot.clone()]];
+
+        // The duplicate dot adds no moves
+        let moves = game.get_hand_moves();
+        let expected = game.get_valid_moves(&dot).len() + game.get_valid_moves(&bar).len();
+        assert_eq!(moves.len(), expected);
+        assert!(moves.iter().all(|hand_move| hand_move.piece_index < 2));
+
+        assert!(!game.is_valid_hand_move(HandMove { piece_index: 5, orientation: 0, x: 0, y: 0 }));
+        assert!(!game.is_valid_hand_move(HandMove { piece_index: 1, orientation: 1, x: 0, y: 0 }));
+        let hand_move = moves.iter().copied().find(|hand_move| hand_move.piece_index == 1).unwrap();
+        let played = game.play_hand_move(hand_move).unwrap();
+        assert_eq!(played.shape, bar.shape);
+        assert_eq!(game.hand(1).len(), 2);
+        assert_eq!(game.calculate_scores().0, 3);
+        game.switch_player();
+
+        let mut ais: Vec<Box<dyn AIPlayer>> = vec![
+            Box::new(RandomAI::with_seed(1)),
+            Box::new(GreedyAI::new()),
+            Box::new(MinimaxAI::new(3)),
+        ];
+        for ai in &mut ais {
+            let chosen = ai.choose_hand_move(&game).unwrap();
+            assert!(game.is_valid_hand_move(chosen));
+        }
+
+        // Greedy prefers the piece that gains more territory
+        let chosen = GreedyAI::new().choose_hand_move(&game).unwrap();
+        assert_eq!(chosen.piece_index, 0);
+
+        // With rotation the bar may also stand upright
+        game.piece_rules = PieceRules { rotate: true, mirror: false };
+        let moves = game.get_hand_moves();
+        assert!(moves.iter().any(|hand_move| hand_move.piece_index == 0 && hand_move.orientation == 1));
+        assert!(moves.iter().all(|&hand_move| game.is_valid_hand_move(hand_move)));
+        let analysis = MinimaxAI::new(2).analyze_hand(&game);
+        assert_eq!(analysis.hand_moves.len(), moves.len());
+        assert_eq!(analysis.best_hand_move, analysis.hand_moves.first().copied());
+        let best = analysis.best_hand_move.unwrap();
+        assert_eq!(analysis.best_move, Some((best.x, best.y)));
+    }
+
+    #[test]
+    fn test_rotatable_pieces() {
+        let l_piece = Piece::new(vec![(0, 0), (0, 1), (0, 2), (1, 2)]);
+        assert_eq!(l_piece.oriented(0).shape, l_piece.shape);
+        assert_eq!(l_piece.oriented(1).shape, l_piece.rotated().shape);
+        assert_eq!(l_piece.oriented(4).shape, l_piece.mirrored().shape);
+
+        let mut game = GameState::new(10, 10);
+        let fixed = game.get_oriented_moves(&l_piece);
+        assert_eq!(fixed.len(), game.get_valid_moves(&l_piece).len());
+        assert!(fixed.iter().all(|oriented_move| oriented_move.orientation == 0));
+
+        game.piece_rules = PieceRules { rotate: true, mirror: false };
+        let rotations = game.get_oriented_moves(&l_piece);
+        game.piece_rules.mirror = true;
+        let all = game.get_oriented_moves(&l_piece);
+        assert!(fixed.len() < rotations.len() && rotations.len() < all.len());
+        assert!(all.iter().any(|oriented_move| oriented_move.orientation >= 4));
+
+        // Orientations outside the rules are rejected
+        game.piece_rules = PieceRules { rotate: true, mirror: false };
+        let mirrored = all.iter().copied().find(|oriented_move| oriented_move.orientation >= 4).unwrap();
+        assert!(!game.is_valid_oriented_move(&l_piece, mirrored));
+
+        let mut ais: Vec<Box<dyn AIPlayer>> = vec![
+            Box::new(RandomAI::with_seed(2)),
+            Box::new(GreedyAI::new()),
+            Box::new(MinimaxAI::new(2)),
+        ];
+        for ai in &mut ais {
+            let chosen = ai.choose_oriented_move(&game, &l_piece).unwrap();
+            assert!(game.is_valid_oriented_move(&l_piece, chosen));
+        }
+
+        let chosen = rotations[0];
+        assert!(game.place_oriented(&l_piece, chosen));
+        assert_eq!(game.calculate_scores().0, 4);
+    }
+
+    #[test]
+    fn test_multiplayer_turns_and_elimination() {
+        let map = "@..%\n....\n....\n&..$\n";
+        let mut game = GameState::from_map_str(map).unwrap();
+        assert_eq!(game.num_players, 4);
+        assert_eq!(game.player_scores(), vec![1, 1, 1, 1]);
+        assert!(GameState::from_map_str("@..%\n...$\n").is_ok());
+        assert!(GameState::from_map_str("@..&\n...$\n").is_err());
+        // A map needs start cells for at least two players
+        assert!(GameState::from_map_str("@...\n....\n").is_err());
+        assert!(GameState::from_map_str("....\n.#..\n").is_err());
+
+        for expected in [2, 3, 4, 1] {
+            game.switch_player();
+            assert_eq!(game.current_player, expected);
+        }
+
+        // Player 2 is boxed in: they drop out and the others play on
+        game.board[2][3] = Cell::Player3Old;
+        game.board[3][2] = Cell::Player4Old;
+        game.board[2][2] = Cell::Player4Old;
+        let wide = Piece::new(vec![(0, 0), (1, 0)]);
+        game.current_player = 2;
+        assert!(!game.check_game_over(&wide));
+        assert!(game.is_eliminated(2));
+        assert_eq!(game.current_player, 3);
+        game.current_player = 1;
+        game.switch_player();
+        assert_eq!(game.current_player, 3);
+
+        assert!(game.place_piece(&wide, 2, 0));
+        assert_eq!(game.board[0][2], Cell::Player3New);
+        assert_eq!(game.player_scores(), vec![1, 1, 3, 3]);
+        game.end_game();
+        assert_eq!(game.winner, None);
+        game.board[1][0] = Cell::Player4Old;
+        game.end_game();
+        assert_eq!(game.winner, Some(4));
+    }
+
+    #[test]
+    fn test_minimax_against_several_opponents() {
+        let game = GameState::with_players(12, 12, 3);
+        assert_eq!(game.board[0][11], Cell::Player3Old);
+
+        let mut minimax = MinimaxAI::new(2);
+        let mut greedy = GreedyAI::new();
+        let mut random = RandomAI::with_seed(7);
+        let final_state = play_headless_match(&game, &mut [&mut minimax, &mut greedy, &mut random], 3);
+        assert!(final_state.game_over);
+        assert_eq!(final_state.player_scores().len(), 3);
+        assert!(final_state.player_scores().iter().all(|&score| score > 1));
+    }
+
+    #[test]
+    fn test_team_scoring() {
+        let map = "teams: 1 2 1 2\n@@.%\n....\n....\n&..$\n";
+        let mut game = GameState::from_map_str(map).unwrap();
+        assert!(game.is_team_game());
+        assert!(game.are_teammates(1, 3) && !game.are_teammates(1, 2));
+        assert_eq!(game.team_scores(), vec![3, 2]);
+        assert_eq!(game.side_score(3), 3);
+        assert_eq!(game.best_opponent_score(1), 2);
+        assert!(game.set_teams(vec![1, 1, 1, 1]).is_err());
+        assert!(GameState::from_map_str("teams: 1 2\n@..%\n...$\n").is_err());
+
+        // Teammates cannot extend from each other's territory
+        let domino = Piece::new(vec![(0, 0), (1, 0)]);
+        game.current_player = 3;
+        assert!(!game.is_valid_move(&domino, 1, 0));
+
+        // No single player leads, but team 2 has more territory
+        game.board[3][1] = Cell::Player2Old;
+        game.board[3][2] = Cell::Player4Old;
+        game.end_game();
+        assert_eq!(game.winner, None);
+        assert_eq!(game.winning_team, Some(2));
+        assert_eq!(game.result_text(), "Team 2 wins");
+
+        // The game lasts while both teams have someone able to move
+        let mut game = GameState::from_map_str(map).unwrap();
+        game.eliminated[1] = true;
+        game.current_player = 1;
+        assert!(!game.eliminate_current());
+        assert!(game.eliminate_current());
+    }
+
+    #[test]
+    fn test_minimax_in_team_game() {
+        let mut game = GameState::with_players(12, 12, 4);
+        game.set_teams(vec![1, 2, 1, 2]).unwrap();
+
+        let mut minimax = MinimaxAI::new(2);
+        let mut greedy = GreedyAI::new();
+        let mut partner = MinimaxAI::new(1);
+        let mut random = RandomAI::with_seed(7);
+        let final_state = play_headless_match(
+            &game,
+            &mut [&mut minimax, &mut greedy, &mut partner, &mut random],
+            3,
+        );
+        assert!(final_state.game_over);
+        let scores = final_state.team_scores();
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores.iter().sum::<u32>(), final_state.player_scores().iter().sum::<u32>());
+
+        // The partner's territory counts towards the centre and compactness.
+        // Only the layout of player 3's nine cells differs, and a single cell
+        // on player 1's start changes nothing, so only position is scored
+        let team_map = "@.......&\n.........\n.........\n.........\n.........\n\
+                        .........\n.........\n.........\n%.......$\nteams: 1 2 1 2\n";
+        let dot = Piece::new(vec![(0, 0)]);
+        let score_with = |cells: &[(usize, usize)]| {
+            let mut game = GameState::from_map_str(team_map).unwrap();
+            for &(x, y) in cells {
+                game.board[y][x] = Cell::Player3Old;
+            }
+            MinimaxAI::new(1).analyze(&game, &dot).move_scores[0].1
+        };
+        let block = |left: usize, top: usize| -> Vec<(usize, usize)> {
+            (0..9).map(|i| (left + i % 3, top + i / 3)).collect()
+        };
+        let scattered: Vec<(usize, usize)> = (0..9).map(|i| (2 * (i % 3) + 2, [0, 2, 6][i / 3])).collect();
+        let (centre, edge) = (score_with(&block(3, 3)), score_with(&block(5, 0)));
+        assert!(centre > edge, "centre {} edge {}", centre, edge);
+        assert!(edge > score_with(&scattered));
+    }
+
+    #[test]
+    fn test_blocked_cells_and_irregular_maps() {
+        let map = "  @..\n.#...\n....$\n";
+        let mut game = GameState::from_map_str(map).unwrap();
+        assert_eq!((game.width, game.height), (5, 3));
+        assert_eq!(game.board[0][0], Cell::Blocked);
+        assert_eq!(game.board[1][1], Cell::Blocked);
+        assert_eq!(game.playable_cells(), 12);
+
+        // Short rows are walled off at the end
+        let ragged = GameState::from_map_str("@...\n..\n...$\n").unwrap();
+        assert_eq!(ragged.board[1][3], Cell::Blocked);
+
+        // No piece may cover a wall
+        let column = Piece::new(vec![(0, 0), (0, 1)]);
+        assert!(!game.is_valid_move(&Piece::new(vec![(0, 0), (1, 0)]), 1, 0));
+        assert!(game.is_valid_move(&column, 2, 0));
+        game.board[1][2] = Cell::Blocked;
+        assert!(!game.is_valid_move(&column, 2, 0));
+        assert_eq!(game.display_board().matches('#').count(), 4);
+
+        let mut minimax = MinimaxAI::new(2);
+        for (x, y) in game.get_valid_moves(&column) {
+            assert!(column.shape.iter().all(|(px, py)| game.board[y + py][x + px] != Cell::Blocked));
+        }
+        if let Some((x, y)) = minimax.choose_move(&game, &column) {
+            assert!(game.place_piece(&column, x, y));
+        }
+        assert_eq!(game.playable_cells(), 11);
+    }
+
+    #[test]
+    fn test_toroidal_board() {
+        let mut game = GameState::from_map_str("...@\n....\n$...\n").unwrap();
+        let domino = Piece::new(vec![(0, 0), (1, 0)]);
+        assert!(!game.is_valid_move(&domino, 3, 0));
+        assert_eq!(game.cell_offset(0, 0, -1, -1), None);
+
+        game.topology = Topology::Torus;
+        assert_eq!(game.cell_offset(0, 0, -1, -1), Some((3, 2)));
+        assert_eq!(game.piece_cells(&domino, 3, 0), Some(vec![(3, 0), (0, 0)]));
+        assert!(game.place_piece(&domino, 3, 0));
+        assert_eq!(game.board[0][0], Cell::Player1New);
+
+        // Hanging off the bottom continues at the top, into player 1
+        let column = Piece::new(vec![(0, 0), (0, 1)]);
+        game.switch_player();
+        assert!(!game.is_valid_move(&column, 0, 2));
+        assert!(game.is_valid_move(&domino, 3, 2));
+
+        // A piece longer than the board would overlap itself
+        let long = Piece::new(vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+        assert_eq!(game.piece_cells(&long, 0, 0), None);
+
+        let mut ai = MinimaxAI::new(2);
+        let (x, y) = ai.choose_move(&game, &column).unwrap();
+        assert!(game.place_piece(&column, x, y));
+    }
+
+    #[test]
+    fn test_map_generator() {
+        let mut spec = MapSpec::new(21, 14);
+        spec.obstacle_density = 0.2;
+        spec.seed = 11;
+        for (players, symmetry) in [(2, MapSymmetry::Rotational), (2, MapSymmetry::Mirror), (4, MapSymmetry::Mirror)] {
+            spec.players = players;
+            spec.symmetry = symmetry;
+            let game = generate_map(&spec).unwrap();
+            assert_eq!(game.num_players, players);
+            assert!(game.board.iter().flatten().any(|cell| *cell == Cell::Blocked));
+
+            // Walls and empty cells look the same from every image
+            for y in 0..game.height {
+                for x in 0..game.width {
+                    let blocked = game.board[y][x] == Cell::Blocked;
+                    for (ix, iy) in symmetry.orbit(x, y, game.width, game.height, players) {
+                        assert_eq!(game.board[iy][ix] == Cell::Blocked, blocked);
+                    }
+                }
+            }
+
+            let text = game.to_map_string();
+            assert_eq!(generate_map(&spec).unwrap().to_map_string(), text);
+            assert_eq!(GameState::from_map_str(&text).unwrap().board, game.board);
+        }
+
+        spec.players = 3;
+        assert!(generate_map(&spec).is_err());
+    }
+
+    #[test]
+    fn test_map_fairness_report() {
+        let even = GameState::from_map_str("@....\n.....\n....$\n").unwrap();
+        let report = analyze_map(&even);
+        assert!(report.is_well_formed());
+        assert_eq!(report.start_distances, vec![(1, 2, Some(6))]);
+        assert_eq!(report.closer_cells[0], report.closer_cells[1]);
+        assert_eq!(report.closer_cells[0] * 2 + report.contested_cells, 13);
+        assert_eq!(report.reachable_balance(), 1.0);
+
+        // A wall that shuts player 2 into a corner pocket
+        let uneven = GameState::from_map_str("@....\n...##\n...#$\n").unwrap();
+        let report = analyze_map(&uneven);
+        assert_eq!(report.start_distances, vec![(1, 2, None)]);
+        assert_eq!(report.reachable_cells[1], 0);
+        assert!(!report.is_well_formed());
+
+        let lopsided = GameState::from_map_str("@.......\n......#.\n.....#$.\n").unwrap();
+        assert!(analyze_map(&lopsided).voronoi_balance() < 1.0);
+
+        let selfplay = self_play_fairness(&even, &AIDifficulty::Easy, &[1, 2]);
+        assert_eq!(selfplay.games, 4);
+        assert_eq!(selfplay.seat_wins.iter().sum::<u32>() + selfplay.ties, 4);
+        assert_eq!(selfplay.start_wins.iter().sum::<u32>(), selfplay.seat_wins.iter().sum::<u32>());
+    }
+
+    #[test]
+    fn test_save_and_resume() {
+        let mut game = GameState::load_map("maps/two_vs_two").unwrap();
+        game.topology = Topology::Torus;
+        game.piece_rules = PieceRules { rotate: true, mirror: false };
+        let mut generator = PieceGenerator::with_set(derive_seed(7, SeedStream::Pieces), PieceSet::classic());
+        game.hands = (0..game.num_players).map(|_| (0..3).map(|_| generator.next()).collect()).collect();
+        game.eliminated[2] = true;
+        game.current_player = 2;
+        game.turn = 9;
+
+        let saved = SavedGame {
+            game: game.clone(),
+            seed: 7,
+            pieces_dealt: generator.dealt(),
+            piece_set: "classic".to_string(),
+            preview: 2,
+            players: vec!["human".to_string(), "bot ./my_bot".to_string(), "ai medium".to_string(), "ai medium".to_string()],
+        };
+        let loaded = SavedGame::parse(&saved.to_save_string()).unwrap();
+        assert_eq!(loaded.game.board, game.board);
+        let patterns = |game: &GameState| -> Vec<Vec<String>> {
+            game.hands.iter().map(|hand| hand.iter().map(Piece::to_pattern).collect()).collect()
+        };
+        assert_eq!(patterns(&loaded.game), patterns(&game));
+        assert_eq!(loaded.game.teams, game.teams);
+        assert_eq!(loaded.game.eliminated, game.eliminated);
+        assert_eq!(loaded.game.topology, Topology::Torus);
+        assert_eq!((loaded.game.current_player, loaded.game.turn), (2, 9));
+        assert!(loaded.game.piece_rules.rotate && !loaded.game.piece_rules.mirror);
+        assert_eq!(loaded.players, saved.players);
+        assert_eq!(loaded.preview, 2);
+
+        // The resumed generator deals exactly what the original would have
+        let mut resumed = loaded.piece_generator().unwrap();
+        for _ in 0..10 {
+            assert_eq!(resumed.next().shape, generator.next().shape);
+        }
+
+        assert_eq!(GameCommand::parse("save my game.sav"), Some(GameCommand::Save("my game.sav".to_string())));
+        assert_eq!(GameCommand::parse("LOAD game.sav"), Some(GameCommand::Load("game.sav".to_string())));
+        assert_eq!(GameCommand::parse("save"), None);
+        assert_eq!(GameCommand::parse("5 3"), None);
+
+        let old_version = saved.to_save_string().replace("version = 1", "version = 0");
+        assert!(SavedGame::parse(&old_version).is_err());
+
+        // A session saves its own piece set and takes over the loaded one
+        let path = std::env::temp_dir().join(format!("filler_session_{}.sav", std::process::id()));
+        let path_text = path.to_string_lossy().to_string();
+        let mut session = Session { seed: 7, piece_set: "small".to_string(), preview: 0, seats: saved.players.clone() };
+        assert!(!session.run(GameCommand::Save(path_text.clone()), &mut game, &mut generator, 12));
+        assert_eq!(SavedGame::load(&path).unwrap().piece_set, "small");
+        session.piece_set = "classic".to_string();
+        assert!(session.run(GameCommand::Load(path_text), &mut game, &mut generator, 0));
+        assert_eq!(session.piece_set, "small");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_replay_round_trip() {
+        let mut game = GameState::new(12, 10);
+        let mut replay = Replay::new(&game, 11, vec!["ai easy".to_string(), "ai easy".to_string()]);
+        let mut ai = GreedyAI::new();
+        let mut generator = Piece::generate_sequence(derive_seed(11, SeedStream::Pieces));
+        let mut positions = vec![game.clone()];
+        loop {
+            let piece = generator.next();
+            let eliminated_before = game.eliminated.clone();
+            let game_over = game.check_game_over(&piece);
+            replay.record_dropouts(&eliminated_before, &game, &piece);
+            if game_over {
+                break;
+            }
+            let (x, y) = ai.choose_move(&game, &piece).unwrap();
+            replay.record_move(game.current_player, &piece, x, y);
+            game.place_piece(&piece, x, y);
+            positions.push(game.clone());
+            game.switch_player();
+        }
+
+        let loaded = Replay::parse(&replay.to_replay_string()).unwrap();
+        assert_eq!(loaded.seed, 11);
+        assert_eq!(loaded.players, replay.players);
+        assert_eq!(loaded.moves.len(), replay.moves.len());
+        let states = loaded.states().unwrap();
+        assert_eq!(states.len(), loaded.moves.len() + 1);
+        let placed: Vec<&GameState> = states
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index == 0 || loaded.moves[index - 1].position.is_some())
+            .map(|(_, state)| state)
+            .collect();
+        assert_eq!(placed.len(), positions.len());
+        for (state, position) in placed.iter().zip(&positions) {
+            assert_eq!(state.board, position.board);
+        }
+        let last = states.last().unwrap();
+        assert!(last.game_over);
+        assert_eq!(last.player_scores(), game.player_scores());
+        assert_eq!(last.winner, game.winner);
+
+        // A move that does not fit the position is reported, not replayed
+        let mut broken = loaded.clone();
+        broken.moves[0].position = loaded.moves[1].position;
+        assert!(broken.states().is_err());
+        assert!(Replay::parse("version = 1\n[map]\n@.\n.$\n").is_err());
+        // A seat without a start cell on the board is refused
+        let text = replay.to_replay_string().replace("[map]", "player5 = ai easy\n[map]");
+        assert!(Replay::parse(&text).is_err());
+    }
+
+    #[test]
+    fn test_import_engine_log() {
+        let log = "$$$ exec p1 : [robots/bender]\n$$$ exec p2 : [robots/terminator]\n\
+                   Anfield 5 5:\n    01234\n000 .....\n001 .@...\n002 .....\n003 ...$.\n004 .....\n\
+                   Piece 2 1:\nOO\n-> Answer (@): 1 1\n\n\
+                   Anfield 5 5:\n    01234\n000 .....\n001 .aa..\n002 .....\n003 ...$.\n004 .....\n\
+                   Piece 1 2:\nO\nO\n-> Answer ($): 3 2\n\n\
+                   Anfield 5 5:\n    01234\n000 .....\n001 .aa..\n002 ...s.\n003 ...s.\n004 .....\n\
+                   Piece 1 1:\nO\n-> Answer (@): 4 4\n\nseed: 77\n";
+        let replay = import_engine_log(log).unwrap();
+        assert_eq!(replay.seed, 77);
+        assert_eq!(replay.players, vec!["bot robots/bender", "bot robots/terminator"]);
+        assert_eq!(replay.moves.len(), 3);
+        assert_eq!(replay.moves[1].position, Some((3, 2)));
+        assert_eq!((replay.moves[2].player, replay.moves[2].position), (1, None));
+        let last = replay.final_state().unwrap();
+        assert_eq!(last.player_scores(), vec![2, 2]);
+        assert!(last.is_eliminated(1));
+
+        // The older virtual machine's format: O and X, answers as [row, column]
+        let vm_log = "Plateau 5 5:\n    01234\n000 .....\n001 .O...\n002 .....\n003 ...X.\n004 .....\n\
+                      Piece 1 2:\n*\n*\n<got (O): [1, 1]>\n\
+                      Plateau 5 5:\n    01234\n000 .....\n001 .o...\n002 .o...\n003 ...X.\n004 .....\n\
+                      Piece 1 2:\n*\n*\n<got (X): [2, 3]>\n";
+        let replay = import_engine_log(vm_log).unwrap();
+        assert_eq!(replay.moves[1].position, Some((3, 2)));
+        assert_eq!(replay.final_state().unwrap().player_scores(), vec![2, 2]);
+
+        // A board the moves cannot lead to is an error
+        let broken = log.replacen("001 .aa..", "001 .a.a.", 1);
+        assert!(import_engine_log(&broken).is_err());
+    }
+
+    /// A two-move game on a 5x5 board, imported from an engine log.
+    fn two_move_replay() -> Replay {
+        let log = "Anfield 5 5:\n000 .....\n001 .@...\n002 .....\n003 ...$.\n004 .....\n\
+                   Piece 2 1:\nOO\n-> Answer (@): 1 1\n\
+                   Anfield 5 5:\n000 .....\n001 .aa..\n002 .....\n003 ...$.\n004 .....\n\
+                   Piece 1 2:\nO\nO\n-> Answer ($): 3 2\n";
+        import_engine_log(log).unwrap()
+    }
+
+    #[test]
+    fn test_export_frames() {
+        let replay = two_move_replay();
+        let options = ExportOptions { cell_size: 10, ..ExportOptions::default() };
+
+        let frames = replay_svg_frames(&replay, &options).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].starts_with("<svg") && frames[0].trim_end().ends_with("</svg>"));
+        assert!(frames[2].contains("2/2") && frames[2].contains("P1 2") && frames[2].contains("P2 2"));
+        // The latest piece is outlined; nothing is outlined at the start
+        assert_eq!(frames[0].matches("stroke-width=\"2\"").count(), 0);
+        assert_eq!(frames[2].matches("stroke-width=\"2\"").count(), 2);
+
+        let every_other = ExportOptions { every: 2, ..options.clone() };
+        assert_eq!(replay_svg_frames(&replay, &every_other).unwrap().len(), 2);
+
+        let gif = replay_gif(&replay, &options).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(gif.last(), Some(&0x3b));
+        assert_eq!(u16::from_le_bytes([gif[6], gif[7]]), 50);
+    }
+
+    #[test]
+    fn test_export_html() {
+        let mut replay = two_move_replay();
+        replay.players = vec!["human".to_string(), "bot </script><b>".to_string()];
+
+        let html = replay_html(&replay).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>") && html.trim_end().ends_with("</html>"));
+        assert!(html.contains("\"board\":\"......1...........2......\""));
+        assert!(html.contains("\"moves\":[[1,6,7],[2,13,18]]"));
+        assert!(html.contains("\"scores\":[[1,1],[2,1],[2,2]]"));
+        assert!(html.contains("Player 1 human"));
+        // Nothing is loaded from elsewhere and names cannot end the script
+        assert!(!html.contains("http") && !html.contains(" src="));
+        assert_eq!(html.matches("</script>").count(), 1);
+    }
 }
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+use std::io;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::{Cell, GameState};
+
+/// How a generated map is made fair: every start cell and wall has images
+/// under the symmetry, so all players face the same board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapSymmetry {
+    /// Half-turn rotation about the center (both axes for four players).
+    Rotational,
+    /// Left-right mirror image (both axes for four players).
+    Mirror,
+}
+
+impl MapSymmetry {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rotational" | "rotate" | "rotation" => Some(MapSymmetry::Rotational),
+            "mirror" | "mirrored" => Some(MapSymmetry::Mirror),
+            _ => None,
+        }
+    }
+
+    /// The cells `(x, y)` maps to on a `width` x `height` board for
+    /// `players` players, starting with `(x, y)` itself. With four players
+    /// the images are ordered by the player they belong to.
+    pub fn orbit(&self, x: usize, y: usize, width: usize, height: usize, players: u8) -> Vec<(usize, usize)> {
+        let (mx, my) = (width - 1 - x, height - 1 - y);
+        let mut cells = match (players, self) {
+            (2, MapSymmetry::Rotational) => vec![(x, y), (mx, my)],
+            (2, MapSymmetry::Mirror) => vec![(x, y), (mx, y)],
+            _ => vec![(x, y), (mx, my), (mx, y), (x, my)],
+        };
+        // Cells on an axis are their own image; keep each cell once
+        let mut seen = Vec::new();
+        cells.retain(|cell| {
+            let first = !seen.contains(cell);
+            seen.push(*cell);
+            first
+        });
+        cells
+    }
+}
+
+/// Parameters for `generate_map`.
+#[derive(Debug, Clone)]
+pub struct MapSpec {
+    pub width: usize,
+    pub height: usize,
+    /// 2 or 4: three start cells cannot be placed symmetrically.
+    pub players: u8,
+    pub symmetry: MapSymmetry,
+    /// Fraction of the board to wall off, from 0.0 (open board) upwards.
+    pub obstacle_density: f64,
+    pub seed: u64,
+}
+
+impl MapSpec {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            players: 2,
+            symmetry: MapSymmetry::Rotational,
+            obstacle_density: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+/// Smallest side a generated board may have.
+const MIN_SIDE: usize = 5;
+
+/// Random start orbits tried; the most spread out one is used.
+const START_TRIES: usize = 16;
+
+/// Free cells kept around every start cell so no player starts walled in.
+const START_CLEARANCE: usize = 2;
+
+/// Generates a symmetric map. The same spec always gives the same map.
+pub fn generate_map(spec: &MapSpec) -> io::Result<GameState> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    if spec.width < MIN_SIDE || spec.height < MIN_SIDE {
+        return Err(invalid(format!("maps must be at least {}x{}", MIN_SIDE, MIN_SIDE)));
+    }
+    if spec.players != 2 && spec.players != 4 {
+        return Err(invalid(format!("cannot place {} players symmetrically; use 2 or 4", spec.players)));
+    }
+    if !(0.0..0.6).contains(&spec.obstacle_density) {
+        return Err(invalid("obstacle density must be at least 0 and below 0.6".to_string()));
+    }
+
+    let (width, height) = (spec.width, spec.height);
+    let mut rng = StdRng::seed_from_u64(spec.seed);
+    let orbit = |x: usize, y: usize| spec.symmetry.orbit(x, y, width, height, spec.players);
+
+    // Start cells: one orbit with a cell per player, the most spread out of
+    // a few random tries
+    let spread = |cells: &[(usize, usize)]| {
+        let mut closest = usize::MAX;
+        for (i, a) in cells.iter().enumerate() {
+            for b in &cells[i + 1..] {
+                closest = closest.min(a.0.abs_diff(b.0) + a.1.abs_diff(b.1));
+            }
+        }
+        closest
+    };
+    let mut starts = Vec::new();
+    let mut tries = 0;
+    while tries < START_TRIES || starts.is_empty() {
+        tries += 1;
+        let candidate = orbit(rng.gen_range(0..width), rng.gen_range(0..height));
+        if candidate.len() == spec.players as usize && (starts.is_empty() || spread(&candidate) > spread(&starts)) {
+            starts = candidate;
+        }
+    }
+
+    let mut board = vec![vec![Cell::Empty; width]; height];
+    let near_start = |x: usize, y: usize| {
+        starts.iter().any(|&(sx, sy)| sx.abs_diff(x) <= START_CLEARANCE && sy.abs_diff(y) <= START_CLEARANCE)
+    };
+
+    // Walls: small rectangles, each copied to all of its images
+    let target = (spec.obstacle_density * (width * height) as f64) as usize;
+    let mut walls = 0;
+    let mut attempts = 0;
+    while walls < target && attempts < width * height * 4 {
+        attempts += 1;
+        let (w, h) = (rng.gen_range(1..=3), rng.gen_range(1..=3));
+        let (x0, y0) = (rng.gen_range(0..width), rng.gen_range(0..height));
+        for y in y0..(y0 + h).min(height) {
+            for x in x0..(x0 + w).min(width) {
+                let images = orbit(x, y);
+                if images.iter().any(|&(ix, iy)| near_start(ix, iy)) {
+                    continue;
+                }
+                for (ix, iy) in images {
+                    if board[iy][ix] == Cell::Empty {
+                        board[iy][ix] = Cell::Blocked;
+                        walls += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    for (index, &(x, y)) in starts.iter().enumerate() {
+        board[y][x] = Cell::old_for(index as u8 + 1);
+    }
+    wall_off_unreachable(&mut board, &starts);
+
+    Ok(GameState::from_board(board))
+}
+
+/// Turns empty cells no start cell can reach into walls, so pockets cut
+/// off by obstacles do not count as playable area.
+fn wall_off_unreachable(board: &mut [Vec<Cell>], starts: &[(usize, usize)]) {
+    let height = board.len();
+    let width = board[0].len();
+    let mut reached = vec![vec![false; width]; height];
+    let mut queue: VecDeque<(usize, usize)> = starts.iter().copied().collect();
+    for &(x, y) in starts {
+        reached[y][x] = true;
+    }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            if nx < width && ny < height && !reached[ny][nx] && board[ny][nx] != Cell::Blocked {
+                reached[ny][nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    for (y, row) in board.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            if *cell == Cell::Empty && !reached[y][x] {
+                *cell = Cell::Blocked;
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{Cell, GameState, Piece};
+
+/// Playing style inferred from an opponent's observed placements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpponentStyle {
+    /// Not enough observations, or no clear preference.
+    Unknown,
+    /// Grabs open space: placements border many empty cells.
+    Greedy,
+    /// Heads for the middle of the board.
+    CenterSeeking,
+    /// Plays next to our territory to block it.
+    Aggressive,
+}
+
+/// Features scored for every candidate placement, higher meaning "more so".
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MoveFeatures {
+    /// Empty cells bordering the placed cells.
+    pub open_space: f64,
+    /// Negative mean distance of the placed cells to the board center.
+    pub centrality: f64,
+    /// Cells of opposing sides bordering the placed cells; a teammate's
+    /// territory is not counted.
+    pub contact: f64,
+}
+
+impl MoveFeatures {
+    pub fn of_cells(game: &GameState, cells: &[(usize, usize)], player: u8) -> Self {
+        let mut features = MoveFeatures::default();
+        if cells.is_empty() {
+            return features;
+        }
+
+        let center_x = game.width as f64 / 2.0;
+        let center_y = game.height as f64 / 2.0;
+        let mut distance = 0.0;
+
+        for &(x, y) in cells {
+            distance += (x as f64 - center_x).abs() + (y as f64 - center_y).abs();
+
+            for dy in -1..=1i32 {
+                for dx in -1..=1i32 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    let Some((nx, ny)) = game.cell_offset(x, y, dx, dy) else {
+                        continue;
+                    };
+                    if cells.contains(&(nx, ny)) {
+                        continue;
+                    }
+
+                    match game.board[ny][nx] {
+                        Cell::Empty => features.open_space += 1.0,
+                        ref cell if cell.owner().is_some_and(|p| !game.are_teammates(p, player)) => features.contact += 1.0,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        features.centrality = -distance / cells.len() as f64;
+        features
+    }
+
+    /// Features of placing `piece` at `(x, y)` for `player` in `game`.
+    pub fn of_move(game: &GameState, piece: &Piece, x: usize, y: usize, player: u8) -> Self {
+        let cells: Vec<(usize, usize)> = game
+            .piece_cells(piece, x, y)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&(cx, cy)| game.board[cy][cx].owner() != Some(player))
+            .collect();
+        Self::of_cells(game, &cells, player)
+    }
+
+    fn values(&self) -> [f64; 3] {
+        [self.open_space, self.centrality, self.contact]
+    }
+}
+
+/// Cells `player` owns in `after` but not in `before`: the territory added by
+/// their last placement. The one overlapped cell is already theirs and is
+/// therefore not included.
+pub fn infer_placement(before: &GameState, after: &GameState, player: u8) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+    for y in 0..after.height.min(before.height) {
+        for x in 0..after.width.min(before.width) {
+            if after.board[y][x].owner() == Some(player) && before.board[y][x].owner() != Some(player) {
+                cells.push((x, y));
+            }
+        }
+    }
+    cells
+}
+
+/// Learns which features an opponent prefers from the boards it leaves.
+///
+/// Each placement is ranked against every legal alternative with the same
+/// piece, and the model keeps the average percentile per feature (0.5 means
+/// no preference). Placements seen without their piece are counted but
+/// cannot be ranked.
+#[derive(Debug, Clone)]
+pub struct OpponentModel {
+    pub player: u8,
+    observations: u32,
+    ranked_observations: u32,
+    preference_sums: [f64; 3],
+}
+
+impl OpponentModel {
+    /// Observations needed before predictions are used.
+    pub const MIN_OBSERVATIONS: u32 = 3;
+
+    pub fn new(player: u8) -> Self {
+        Self {
+            player,
+            observations: 0,
+            ranked_observations: 0,
+            preference_sums: [0.0; 3],
+        }
+    }
+
+    /// Records the move that turned `before` into `after`.
+    pub fn observe(&mut self, before: &GameState, after: &GameState, piece: Option<&Piece>) {
+        let cells = infer_placement(before, after, self.player);
+        if cells.is_empty() {
+            return;
+        }
+
+        self.observations += 1;
+        let Some(piece) = piece else {
+            return;
+        };
+
+        let chosen = MoveFeatures::of_cells(before, &cells, self.player).values();
+
+        // Rank the chosen placement against every alternative with the
+        // same piece, as seen by the opponent
+        let mut position = before.clone();
+        position.current_player = self.player;
+        let alternatives: Vec<[f64; 3]> = position
+            .get_valid_moves(piece)
+            .into_iter()
+            .map(|(x, y)| MoveFeatures::of_move(&position, piece, x, y, self.player).values())
+            .collect();
+        if alternatives.len() < 2 {
+            return;
+        }
+
+        self.ranked_observations += 1;
+        for feature in 0..3 {
+            let value = chosen[feature];
+            let below = alternatives.iter().filter(|alt| alt[feature] < value).count() as f64;
+            let equal = alternatives.iter().filter(|alt| alt[feature] == value).count() as f64;
+            // Mid-rank percentile so that ties count as half
+            let percentile = (below + equal / 2.0) / alternatives.len() as f64;
+            self.preference_sums[feature] += percentile;
+        }
+    }
+
+    pub fn observations(&self) -> u32 {
+        self.observations
+    }
+
+    pub fn is_confident(&self) -> bool {
+        self.ranked_observations >= Self::MIN_OBSERVATIONS
+    }
+
+    /// Average percentile of the opponent's moves for open space,
+    /// centrality and contact.
+    pub fn preferences(&self) -> [f64; 3] {
+        if self.ranked_observations == 0 {
+            return [0.5; 3];
+        }
+        let n = self.ranked_observations as f64;
+        [
+            self.preference_sums[0] / n,
+            self.preference_sums[1] / n,
+            self.preference_sums[2] / n,
+        ]
+    }
+
+    pub fn style(&self) -> OpponentStyle {
+        if !self.is_confident() {
+            return OpponentStyle::Unknown;
+        }
+
+        let preferences = self.preferences();
+        let (index, strongest) = preferences
+            .iter()
+            .enumerate()
+            .fold((0, f64::MIN), |best, (i, &p)| if p > best.1 { (i, p) } else { best });
+
+        if strongest < 0.6 {
+            return OpponentStyle::Unknown;
+        }
+        match index {
+            0 => OpponentStyle::Greedy,
+            1 => OpponentStyle::CenterSeeking,
+            _ => OpponentStyle::Aggressive,
+        }
+    }
+
+    /// Orders `moves` by how likely the opponent is to play them, most
+    /// likely first. Keeps the original order until the model is confident.
+    pub fn rank_moves(&self, game: &GameState, piece: &Piece, moves: &mut [(usize, usize)]) {
+        if !self.is_confident() || moves.len() < 2 {
+            return;
+        }
+
+        let features: Vec<[f64; 3]> = moves
+            .iter()
+            .map(|&(x, y)| MoveFeatures::of_move(game, piece, x, y, self.player).values())
+            .collect();
+        // Features the opponent ignores sit near 0.5 and contribute nothing
+        let weights = self.preferences().map(|p| p - 0.5);
+
+        // Standardize each feature over the candidates so they are comparable
+        let count = features.len() as f64;
+        let mut mean = [0.0; 3];
+        let mut spread = [0.0; 3];
+        for feature in 0..3 {
+            mean[feature] = features.iter().map(|f| f[feature]).sum::<f64>() / count;
+            let variance = features.iter().map(|f| (f[feature] - mean[feature]).powi(2)).sum::<f64>() / count;
+            spread[feature] = variance.sqrt().max(1e-9);
+        }
+
+        let mut scored: Vec<(f64, (usize, usize))> = moves
+            .iter()
+            .zip(&features)
+            .map(|(&mv, values)| {
+                let score = (0..3)
+                    .map(|feature| weights[feature] * (values[feature] - mean[feature]) / spread[feature])
+                    .sum();
+                (score, mv)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (slot, (_, mv)) in moves.iter_mut().zip(scored) {
+            *slot = mv;
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
 use rand::Rng;
 
 #[derive(Debug, Clone)]
@@ -15,6 +19,154 @@ impl Piece {
         Self { shape, width, height }
     }
 
+    /// Builds a piece from arbitrary integer offsets, shifted so that the
+    /// smallest x and y are 0 and with the cells sorted.
+    pub fn from_offsets<I: IntoIterator<Item = (i32, i32)>>(offsets: I) -> Self {
+        let offsets: Vec<(i32, i32)> = offsets.into_iter().collect();
+        let min_x = offsets.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let min_y = offsets.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let mut shape: Vec<(usize, usize)> = offsets
+            .into_iter()
+            .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize))
+            .collect();
+        shape.sort_by_key(|&(x, y)| (y, x));
+        shape.dedup();
+        Self::new(shape)
+    }
+
+    /// The same shape moved to the origin, with its cells in row order.
+    pub fn normalized(&self) -> Self {
+        Self::from_offsets(self.offsets())
+    }
+
+    /// Rotated a quarter turn clockwise.
+    pub fn rotated(&self) -> Self {
+        Self::from_offsets(self.offsets().map(|(x, y)| (-y, x)))
+    }
+
+    /// Mirrored left to right.
+    pub fn mirrored(&self) -> Self {
+        Self::from_offsets(self.offsets().map(|(x, y)| (-x, y)))
+    }
+
+    /// The four rotations followed by the four rotations of the mirror
+    /// image; duplicates are kept so index `i` is always the same transform.
+    pub fn symmetries(&self) -> Vec<Self> {
+        let mut result = Vec::with_capacity(8);
+        for start in [self.normalized(), self.mirrored()] {
+            let mut current = start;
+            for _ in 0..4 {
+                let next = current.rotated();
+                result.push(current);
+                current = next;
+            }
+        }
+        result
+    }
+
+    /// The piece in orientation `orientation` of `symmetries`: `o % 4`
+    /// quarter turns clockwise, mirrored first when `o >= 4`. Orientation 0
+    /// is the piece exactly as dealt.
+    pub fn oriented(&self, orientation: usize) -> Self {
+        if orientation == 0 {
+            return self.clone();
+        }
+        let mut piece = if orientation >= 4 { self.mirrored() } else { self.normalized() };
+        for _ in 0..orientation % 4 {
+            piece = piece.rotated();
+        }
+        piece
+    }
+
+    /// The distinct orientations of this piece.
+    pub fn variants(&self) -> Vec<Self> {
+        let mut result: Vec<Self> = Vec::new();
+        for variant in self.symmetries() {
+            if !result.iter().any(|existing| existing.shape == variant.shape) {
+                result.push(variant);
+            }
+        }
+        result
+    }
+
+    /// Key shared by all translations of this shape (fixed polyomino
+    /// equivalence).
+    pub fn fixed_form(&self) -> Vec<(usize, usize)> {
+        self.normalized().shape
+    }
+
+    /// Key shared by all translations, rotations and reflections of this
+    /// shape (free polyomino equivalence).
+    pub fn free_form(&self) -> Vec<(usize, usize)> {
+        self.symmetries()
+            .into_iter()
+            .map(|variant| variant.shape)
+            .min()
+            .unwrap_or_default()
+    }
+
+    pub fn same_fixed_shape(&self, other: &Piece) -> bool {
+        self.fixed_form() == other.fixed_form()
+    }
+
+    pub fn same_free_shape(&self, other: &Piece) -> bool {
+        self.free_form() == other.free_form()
+    }
+
+    /// True if every cell can be reached from every other through
+    /// edge-adjacent cells.
+    pub fn is_connected(&self) -> bool {
+        let Some(&first) = self.shape.first() else {
+            return false;
+        };
+        let mut reached = vec![first];
+        let mut frontier = vec![first];
+        while let Some((x, y)) = frontier.pop() {
+            for &cell in &self.shape {
+                if !reached.contains(&cell) && cell.0.abs_diff(x) + cell.1.abs_diff(y) == 1 {
+                    reached.push(cell);
+                    frontier.push(cell);
+                }
+            }
+        }
+        reached.len() == self.shape.len()
+    }
+
+    fn offsets(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.shape.iter().map(|&(x, y)| (x as i32, y as i32))
+    }
+
+    /// Every fixed polyomino of `size` cells, each normalized, in a stable
+    /// order. There are 1, 2, 6, 19, 63, 216, 760, ... of them.
+    pub fn fixed_polyominoes(size: usize) -> Vec<Self> {
+        if size == 0 {
+            return Vec::new();
+        }
+
+        let mut current = vec![Self::new(vec![(0, 0)])];
+        for _ in 1..size {
+            let mut seen = HashSet::new();
+            let mut next = Vec::new();
+            for piece in &current {
+                for (x, y) in piece.offsets() {
+                    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        let cell = (x + dx, y + dy);
+                        if piece.offsets().any(|existing| existing == cell) {
+                            continue;
+                        }
+                        let grown = Self::from_offsets(piece.offsets().chain(std::iter::once(cell)));
+                        if seen.insert(grown.shape.clone()) {
+                            next.push(grown);
+                        }
+                    }
+                }
+            }
+            next.sort_by(|a, b| a.shape.cmp(&b.shape));
+            current = next;
+        }
+        current
+    }
+
     pub fn display(&self) -> String {
         let mut result = String::new();
         
@@ -33,85 +185,329 @@ impl Piece {
         result
     }
 
-    // Tetris-like piece shapes
-    pub fn generate_random() -> Self {
-        let mut rng = rand::thread_rng();
-        let piece_type = rng.gen_range(0..19);
-        
-        match piece_type {
+    /// The piece as `*`/`.` rows joined by `/`, e.g. `**/.*`, for one-line
+    /// text formats such as save files.
+    pub fn to_pattern(&self) -> String {
+        let rows: Vec<String> = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| if self.shape.contains(&(x, y)) { '*' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        rows.join("/")
+    }
+
+    /// Reads a piece written by `to_pattern`.
+    pub fn from_pattern(pattern: &str) -> io::Result<Piece> {
+        let rows: Vec<&str> = pattern.trim().split('/').collect();
+        parse_shape(&rows, 1)
+    }
+
+    /// A random piece from the classic set, drawn from `rng`. Pass a
+    /// seeded RNG, e.g. from `derive_seed`, to keep games reproducible.
+    pub fn generate_random_with<R: Rng>(rng: &mut R) -> Self {
+        PieceSet::classic().draw(rng)
+    }
+
+    pub fn generate_sequence(seed: u64) -> PieceGenerator {
+        PieceGenerator::new(seed)
+    }
+}
+
+/// The pieces a game hands out and how likely each one is.
+#[derive(Debug, Clone)]
+pub enum PieceSet {
+    /// Fixed shapes, each drawn with probability proportional to its weight.
+    Weighted(Vec<(Piece, u32)>),
+    /// Random connected shapes of 1 to `max_cells` cells, like the official
+    /// engine's irregular pieces.
+    RandomPolyominoes { max_cells: usize },
+}
+
+impl PieceSet {
+    pub const PRESET_NAMES: [&'static str; 3] = ["classic", "official", "small"];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::classic()),
+            "official" => Some(Self::official_like()),
+            "small" => Some(Self::small_only()),
+            _ => None,
+        }
+    }
+
+    /// The 19 tetromino-like shapes, drawn uniformly.
+    pub fn classic() -> Self {
+        let shapes = vec![
             // Single dot
-            0 => Self::new(vec![(0, 0)]),
-            
+            vec![(0, 0)],
             // Line pieces
-            1 => Self::new(vec![(0, 0), (1, 0)]), // 2x1 horizontal
-            2 => Self::new(vec![(0, 0), (0, 1)]), // 1x2 vertical
-            3 => Self::new(vec![(0, 0), (1, 0), (2, 0)]), // 3x1 horizontal
-            4 => Self::new(vec![(0, 0), (0, 1), (0, 2)]), // 1x3 vertical
-            5 => Self::new(vec![(0, 0), (1, 0), (2, 0), (3, 0)]), // 4x1 horizontal
-            6 => Self::new(vec![(0, 0), (0, 1), (0, 2), (0, 3)]), // 1x4 vertical
-            
+            vec![(0, 0), (1, 0)],
+            vec![(0, 0), (0, 1)],
+            vec![(0, 0), (1, 0), (2, 0)],
+            vec![(0, 0), (0, 1), (0, 2)],
+            vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+            vec![(0, 0), (0, 1), (0, 2), (0, 3)],
             // Square pieces
-            7 => Self::new(vec![(0, 0), (1, 0), (0, 1), (1, 1)]), // 2x2 square
-            8 => Self::new(vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]), // 3x2 rectangle
-            
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)],
+            vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)],
             // L-shaped pieces
-            9 => Self::new(vec![(0, 0), (0, 1), (0, 2), (1, 2)]), // L
-            10 => Self::new(vec![(0, 0), (1, 0), (2, 0), (0, 1)]), // L rotated
-            11 => Self::new(vec![(1, 0), (1, 1), (1, 2), (0, 2)]), // L mirrored
-            12 => Self::new(vec![(0, 0), (0, 1), (1, 1), (2, 1)]), // L rotated mirrored
-            
+            vec![(0, 0), (0, 1), (0, 2), (1, 2)],
+            vec![(0, 0), (1, 0), (2, 0), (0, 1)],
+            vec![(1, 0), (1, 1), (1, 2), (0, 2)],
+            vec![(0, 0), (0, 1), (1, 1), (2, 1)],
             // T-shaped pieces
-            13 => Self::new(vec![(0, 0), (1, 0), (2, 0), (1, 1)]), // T
-            14 => Self::new(vec![(0, 0), (0, 1), (0, 2), (1, 1)]), // T rotated
-            15 => Self::new(vec![(1, 0), (0, 1), (1, 1), (2, 1)]), // T upside down
-            16 => Self::new(vec![(1, 0), (1, 1), (1, 2), (0, 1)]), // T rotated left
-            
+            vec![(0, 0), (1, 0), (2, 0), (1, 1)],
+            vec![(0, 0), (0, 1), (0, 2), (1, 1)],
+            vec![(1, 0), (0, 1), (1, 1), (2, 1)],
+            vec![(1, 0), (1, 1), (1, 2), (0, 1)],
             // Z-shaped pieces
-            17 => Self::new(vec![(0, 0), (1, 0), (1, 1), (2, 1)]), // Z
-            _ => Self::new(vec![(1, 0), (2, 0), (0, 1), (1, 1)]), // Z mirrored
+            vec![(0, 0), (1, 0), (1, 1), (2, 1)],
+            vec![(1, 0), (2, 0), (0, 1), (1, 1)],
+        ];
+        Self::Weighted(shapes.into_iter().map(|shape| (Piece::new(shape), 1)).collect())
+    }
+
+    /// Irregular pieces of up to 6 cells.
+    pub fn official_like() -> Self {
+        Self::RandomPolyominoes { max_cells: 6 }
+    }
+
+    /// The classic shapes of at most 3 cells.
+    pub fn small_only() -> Self {
+        match Self::classic() {
+            Self::Weighted(pieces) => {
+                Self::Weighted(pieces.into_iter().filter(|(piece, _)| piece.shape.len() <= 3).collect())
+            }
+            other => other,
         }
     }
 
-    pub fn generate_sequence(seed: u64) -> PieceGenerator {
-        PieceGenerator::new(seed)
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parses a piece-set file: blocks of `*` (cell) and `.` (empty) rows
+    /// separated by blank lines, each optionally headed by `weight = N`.
+    /// Lines starting with `#` are comments. The result is validated, with
+    /// problems reported against the line each piece starts on.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut pieces = Vec::new();
+        let mut piece_lines = Vec::new();
+        let mut rows: Vec<&str> = Vec::new();
+        let mut weight = 1;
+        let mut block_start = 0;
+
+        let lines: Vec<&str> = text.lines().chain(std::iter::once("")).collect();
+        for (line_no, raw_line) in lines.iter().enumerate() {
+            let line = raw_line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+
+            if line.is_empty() {
+                if !rows.is_empty() {
+                    pieces.push((parse_shape(&rows, block_start)?, weight));
+                    piece_lines.push(block_start);
+                }
+                rows.clear();
+                weight = 1;
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() != "weight" || !rows.is_empty() {
+                    return Err(invalid(format!("line {}: unexpected '{}'", line_no + 1, line)));
+                }
+                weight = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: invalid weight '{}'", line_no + 1, value.trim())))?;
+                continue;
+            }
+            if rows.is_empty() {
+                block_start = line_no + 1;
+            }
+            rows.push(line);
+        }
+
+        check_pieces(&pieces, |index| format!("line {}", piece_lines[index]))?;
+        Ok(Self::Weighted(pieces))
+    }
+
+    /// Checks that the set can be drawn from and that every piece is a
+    /// single connected shape listed only once.
+    pub fn validate(&self) -> io::Result<()> {
+        match self {
+            Self::Weighted(pieces) => check_pieces(pieces, |index| format!("piece {}", index + 1)),
+            Self::RandomPolyominoes { max_cells: 0 } => {
+                Err(invalid("random polyominoes need at least 1 cell".to_string()))
+            }
+            Self::RandomPolyominoes { .. } => Ok(()),
+        }
+    }
+
+    /// The `count` pieces most likely to be dealt, likeliest first. Every
+    /// size of random polyomino is equally likely and small sizes have the
+    /// fewest shapes, so those sets give their smallest shapes.
+    pub fn likely_pieces(&self, count: usize) -> Vec<Piece> {
+        match self {
+            Self::Weighted(pieces) => {
+                let mut ranked: Vec<&(Piece, u32)> = pieces.iter().filter(|(_, weight)| *weight > 0).collect();
+                ranked.sort_by_key(|(_, weight)| std::cmp::Reverse(*weight));
+                ranked.into_iter().take(count).map(|(piece, _)| piece.clone()).collect()
+            }
+            Self::RandomPolyominoes { max_cells } => {
+                (1..=(*max_cells).max(1)).flat_map(Piece::fixed_polyominoes).take(count).collect()
+            }
+        }
+    }
+
+    pub fn draw<R: Rng>(&self, rng: &mut R) -> Piece {
+        match self {
+            Self::Weighted(pieces) => {
+                let total: u32 = pieces.iter().map(|(_, weight)| weight).sum();
+                let mut roll = rng.gen_range(0..total.max(1));
+                for (piece, weight) in pieces {
+                    if roll < *weight {
+                        return piece.clone();
+                    }
+                    roll -= weight;
+                }
+                pieces.last().map(|(piece, _)| piece.clone()).unwrap_or_else(|| Piece::new(vec![(0, 0)]))
+            }
+            Self::RandomPolyominoes { max_cells } => random_polyomino(rng, (*max_cells).max(1)),
+        }
     }
 }
 
+/// Grows a connected shape one random neighbour at a time.
+fn random_polyomino<R: Rng>(rng: &mut R, max_cells: usize) -> Piece {
+    let size = rng.gen_range(1..=max_cells);
+    let mut cells: Vec<(i32, i32)> = vec![(0, 0)];
+
+    while cells.len() < size {
+        let (x, y) = cells[rng.gen_range(0..cells.len())];
+        let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][rng.gen_range(0..4)];
+        let neighbour = (x + dx, y + dy);
+        if !cells.contains(&neighbour) {
+            cells.push(neighbour);
+        }
+    }
+
+    Piece::from_offsets(cells)
+}
+
+fn check_pieces(pieces: &[(Piece, u32)], label: impl Fn(usize) -> String) -> io::Result<()> {
+    if pieces.is_empty() {
+        return Err(invalid("piece set contains no pieces".to_string()));
+    }
+    if pieces.iter().all(|(_, weight)| *weight == 0) {
+        return Err(invalid("every piece has weight 0".to_string()));
+    }
+    if pieces.iter().try_fold(0u32, |total, (_, weight)| total.checked_add(*weight)).is_none() {
+        return Err(invalid(format!("weights add up to more than {}", u32::MAX)));
+    }
+
+    for (index, (piece, _)) in pieces.iter().enumerate() {
+        if piece.shape.is_empty() {
+            return Err(invalid(format!("{}: piece has no cells", label(index))));
+        }
+        if !piece.is_connected() {
+            return Err(invalid(format!("{}: piece is not connected", label(index))));
+        }
+        if let Some(first) = pieces[..index].iter().position(|(other, _)| other.same_fixed_shape(piece)) {
+            return Err(invalid(format!("{}: same shape as {}", label(index), label(first))));
+        }
+    }
+    Ok(())
+}
+
+fn parse_shape(rows: &[&str], first_line: usize) -> io::Result<Piece> {
+    let mut shape = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, ch) in row.chars().enumerate() {
+            match ch {
+                '*' => shape.push((x, y)),
+                '.' => {}
+                other => {
+                    return Err(invalid(format!(
+                        "line {}: invalid character '{}' in piece",
+                        first_line + y,
+                        other
+                    )))
+                }
+            }
+        }
+    }
+    if shape.is_empty() {
+        return Err(invalid(format!("line {}: piece has no cells", first_line)));
+    }
+    Ok(Piece::new(shape))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
 pub struct PieceGenerator {
     rng: rand::rngs::StdRng,
+    set: PieceSet,
+    /// Pieces drawn early by `preview`, dealt before any new draw.
+    queued: VecDeque<Piece>,
+    dealt: usize,
 }
 
 impl PieceGenerator {
+    /// A generator over the classic piece set.
     pub fn new(seed: u64) -> Self {
+        Self::with_set(seed, PieceSet::classic())
+    }
+
+    pub fn with_set(seed: u64, set: PieceSet) -> Self {
         use rand::SeedableRng;
         Self {
             rng: rand::rngs::StdRng::seed_from_u64(seed),
+            set,
+            queued: VecDeque::new(),
+            dealt: 0,
         }
     }
 
+    pub fn piece_set(&self) -> &PieceSet {
+        &self.set
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Piece {
-        let piece_type = self.rng.gen_range(0..19);
-        
-        match piece_type {
-            0 => Piece::new(vec![(0, 0)]),
-            1 => Piece::new(vec![(0, 0), (1, 0)]),
-            2 => Piece::new(vec![(0, 0), (0, 1)]),
-            3 => Piece::new(vec![(0, 0), (1, 0), (2, 0)]),
-            4 => Piece::new(vec![(0, 0), (0, 1), (0, 2)]),
-            5 => Piece::new(vec![(0, 0), (1, 0), (2, 0), (3, 0)]),
-            6 => Piece::new(vec![(0, 0), (0, 1), (0, 2), (0, 3)]),
-            7 => Piece::new(vec![(0, 0), (1, 0), (0, 1), (1, 1)]),
-            8 => Piece::new(vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]),
-            9 => Piece::new(vec![(0, 0), (0, 1), (0, 2), (1, 2)]),
-            10 => Piece::new(vec![(0, 0), (1, 0), (2, 0), (0, 1)]),
-            11 => Piece::new(vec![(1, 0), (1, 1), (1, 2), (0, 2)]),
-            12 => Piece::new(vec![(0, 0), (0, 1), (1, 1), (2, 1)]),
-            13 => Piece::new(vec![(0, 0), (1, 0), (2, 0), (1, 1)]),
-            14 => Piece::new(vec![(0, 0), (0, 1), (0, 2), (1, 1)]),
-            15 => Piece::new(vec![(1, 0), (0, 1), (1, 1), (2, 1)]),
-            16 => Piece::new(vec![(1, 0), (1, 1), (1, 2), (0, 1)]),
-            17 => Piece::new(vec![(0, 0), (1, 0), (1, 1), (2, 1)]),
-            _ => Piece::new(vec![(1, 0), (2, 0), (0, 1), (1, 1)]),
+        self.dealt += 1;
+        match self.queued.pop_front() {
+            Some(piece) => piece,
+            None => self.set.draw(&mut self.rng),
+        }
+    }
+
+    /// How many pieces `next` has returned.
+    pub fn dealt(&self) -> usize {
+        self.dealt
+    }
+
+    /// Deals and discards `count` pieces, e.g. to continue a saved game.
+    pub fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.next();
+        }
+    }
+
+    /// The next `count` pieces `next` will return. Peeking does not change
+    /// the sequence.
+    pub fn preview(&mut self, count: usize) -> Vec<Piece> {
+        while self.queued.len() < count {
+            let piece = self.set.draw(&mut self.rng);
+            self.queued.push_back(piece);
         }
+        self.queued.iter().take(count).cloned().collect()
     }
 }
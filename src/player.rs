@@ -1,19 +1,38 @@
 use std::io::{self, Write};
-use crate::{GameState, Piece};
+use crate::{AIPlayer, Cell, GameCommand, GameState, HandMove, MinimaxAI, OrientedMove, Piece};
 
 pub trait Player {
     fn get_move(&mut self, game: &GameState, piece: &Piece) -> Option<(usize, usize)>;
+    /// Chooses a piece from the hand and where to place it (hand variant).
+    fn get_hand_move(&mut self, game: &GameState) -> Option<HandMove>;
+    /// Chooses an orientation and a position when pieces may be rotated or
+    /// mirrored. Defaults to `get_move` in the dealt orientation.
+    fn get_oriented_move(&mut self, game: &GameState, piece: &Piece) -> Option<OrientedMove> {
+        self.get_move(game, piece).map(|(x, y)| OrientedMove { orientation: 0, x, y })
+    }
     fn get_name(&self) -> &str;
     fn is_human(&self) -> bool;
+    /// A `save` or `load` command entered instead of the last move, if the
+    /// move methods returned `None` because of one.
+    fn take_command(&mut self) -> Option<GameCommand> {
+        None
+    }
 }
 
 pub struct HumanPlayer {
     name: String,
+    hint_ai: Box<dyn AIPlayer>,
+    pending_command: Option<GameCommand>,
 }
 
 impl HumanPlayer {
     pub fn new(name: String) -> Self {
-        Self { name }
+        Self::with_hint_ai(name, Box::new(MinimaxAI::new(2)))
+    }
+
+    /// Uses `hint_ai` to analyse the position for the `hint` command.
+    pub fn with_hint_ai(name: String, hint_ai: Box<dyn AIPlayer>) -> Self {
+        Self { name, hint_ai, pending_command: None }
     }
 
     fn display_game_info(&self, game: &GameState, piece: &Piece) {
@@ -21,10 +40,9 @@ impl HumanPlayer {
         println!("Turn {} - {} ({}) to move", 
                  game.turn, 
                  self.name, 
-                 if game.current_player == 1 { "@" } else { "$" });
+                 Cell::old_for(game.current_player));
         
-        let (p1_score, p2_score) = game.calculate_scores();
-        println!("Scores: Player 1: {} | Player 2: {}", p1_score, p2_score);
+        println!("Scores: {}", score_line(game));
         println!("{}", "=".repeat(60));
         
         // Display board
@@ -35,6 +53,11 @@ impl HumanPlayer {
         println!("Your piece to place:");
         print!("{}", piece.display());
         
+        for (index, upcoming) in game.upcoming.iter().enumerate() {
+            println!("Coming next ({}):", index + 1);
+            print!("{}", upcoming.display());
+        }
+        
         // Display valid moves count
         let valid_moves = game.get_valid_moves(piece);
         println!("Valid moves available: {}", valid_moves.len());
@@ -64,9 +87,11 @@ impl HumanPlayer {
         println!("Commands:");
         println!("• <row> <column> - Place piece at position (e.g., '5 3')");
         println!("• 'help' - Show this help");
-        println!("• 'hint' - Show a suggested move");
+        println!("• 'hint' - Show a suggested move with the AI's analysis");
         println!("• 'valid' - Show all valid moves");
         println!("• 'scores' - Show current scores");
+        println!("• 'save <file>' - Save the game to continue later");
+        println!("• 'load <file>' - Continue a saved game");
         println!("• 'quit' - Quit the game");
         
         let valid_moves = game.get_valid_moves(piece);
@@ -75,41 +100,15 @@ impl HumanPlayer {
         }
     }
 
-    fn show_hint(&self, game: &GameState, piece: &Piece) {
-        let valid_moves = game.get_valid_moves(piece);
-        if valid_moves.is_empty() {
+    fn show_hint(&mut self, game: &GameState, piece: &Piece) {
+        let analysis = self.hint_ai.analyze(game, piece);
+        if analysis.best_move.is_none() {
             println!("No valid moves available!");
             return;
         }
 
-        // Simple heuristic: prefer center positions and larger coverage
-        let mut best_move = valid_moves[0];
-        let mut best_score = self.evaluate_move_simple(game, piece, best_move.0, best_move.1);
-        
-        for &(x, y) in &valid_moves[1..] {
-            let score = self.evaluate_move_simple(game, piece, x, y);
-            if score > best_score {
-                best_score = score;
-                best_move = (x, y);
-            }
-        }
-        
-        println!("💡 Suggested move: {} {} (score: {})", best_move.1, best_move.0, best_score);
-    }
-
-    fn evaluate_move_simple(&self, game: &GameState, piece: &Piece, x: usize, y: usize) -> i32 {
-        let mut score = 0;
-        
-        // Prefer larger pieces
-        score += piece.shape.len() as i32 * 10;
-        
-        // Prefer center positions
-        let center_x = game.width / 2;
-        let center_y = game.height / 2;
-        let distance_to_center = ((x as i32 - center_x as i32).abs() + (y as i32 - center_y as i32).abs()) as i32;
-        score -= distance_to_center;
-        
-        score
+        println!("💡 Hint from {}:", self.hint_ai.get_name());
+        print!("{}", analysis.report(5));
     }
 
     fn show_valid_moves(&self, game: &GameState, piece: &Piece) {
@@ -128,18 +127,69 @@ impl HumanPlayer {
     }
 
     fn show_scores(&self, game: &GameState) {
-        let (p1_score, p2_score) = game.calculate_scores();
+        let scores = game.player_scores();
         println!("Current Scores:");
-        println!("Player 1 (@): {}", p1_score);
-        println!("Player 2 ($): {}", p2_score);
+        for (index, score) in scores.iter().enumerate() {
+            println!("Player {} ({}): {}", index + 1, Cell::old_for(index as u8 + 1), score);
+        }
         
-        let total_cells = game.width * game.height;
-        let empty_cells = total_cells - (p1_score + p2_score) as usize;
+        let total_cells = game.playable_cells();
+        let empty_cells = total_cells - scores.iter().sum::<u32>() as usize;
         println!("Empty cells: {}", empty_cells);
         
-        let percentage_p1 = (p1_score as f32 / total_cells as f32) * 100.0;
-        let percentage_p2 = (p2_score as f32 / total_cells as f32) * 100.0;
-        println!("Territory: P1: {:.1}% | P2: {:.1}%", percentage_p1, percentage_p2);
+        let territory: Vec<String> = scores
+            .iter()
+            .enumerate()
+            .map(|(index, &score)| format!("P{}: {:.1}%", index + 1, score as f32 / total_cells as f32 * 100.0))
+            .collect();
+        println!("Territory: {}", territory.join(" | "));
+    }
+
+    fn display_hand_info(&self, game: &GameState) {
+        println!("\n{}", "=".repeat(60));
+        println!("Turn {} - {} ({}) to move",
+                 game.turn,
+                 self.name,
+                 Cell::old_for(game.current_player));
+
+        println!("Scores: {}", score_line(game));
+        println!("{}", "=".repeat(60));
+
+        println!("\nCurrent Board:");
+        print!("{}", game.display_board());
+
+        println!("Your hand:");
+        for (index, piece) in game.hand(game.current_player).iter().enumerate() {
+            println!("Piece {} ({} valid moves):", index + 1, game.get_valid_moves(piece).len());
+            print!("{}", piece.display());
+            for orientation in game.piece_rules.orientations().into_iter().skip(1) {
+                println!("Piece {} orientation {}:", index + 1, orientation);
+                print!("{}", piece.oriented(orientation).display());
+            }
+        }
+    }
+
+    /// Parses `piece row column [orientation]`, with pieces numbered from 1;
+    /// the orientation defaults to 0.
+    fn parse_hand_move(&self, input: &str) -> Option<HandMove> {
+        let parts: Vec<usize> = input.split_whitespace().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        match *parts.as_slice() {
+            [piece, row, col] if piece > 0 => Some(HandMove { piece_index: piece - 1, orientation: 0, x: col, y: row }),
+            [piece, row, col, orientation] if piece > 0 => {
+                Some(HandMove { piece_index: piece - 1, orientation, x: col, y: row })
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses `row column [orientation]`; the orientation defaults to 0.
+    fn parse_oriented_move(&self, input: &str) -> Option<OrientedMove> {
+        let parts: Vec<usize> = input.split_whitespace().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        match *parts.as_slice() {
+            [row, col] => Some(OrientedMove { orientation: 0, x: col, y: row }),
+            [row, col, orientation] => Some(OrientedMove { orientation, x: col, y: row }),
+            _ => None,
+        }
     }
 
     fn parse_coordinates(&self, input: &str) -> Option<(usize, usize)> {
@@ -162,6 +212,10 @@ impl Player for HumanPlayer {
             
             match self.get_user_input() {
                 Ok(input) => {
+                    if let Some(command) = GameCommand::parse(&input) {
+                        self.pending_command = Some(command);
+                        return None;
+                    }
                     let input_lower = input.to_lowercase();
                     
                     match input_lower.as_str() {
@@ -212,6 +266,93 @@ impl Player for HumanPlayer {
         }
     }
 
+    fn get_hand_move(&mut self, game: &GameState) -> Option<HandMove> {
+        loop {
+            self.display_hand_info(game);
+            if game.piece_rules.is_fixed() {
+                print!("\nEnter move (piece row column), 'hint' or 'quit': ");
+            } else {
+                print!("\nEnter move (piece row column [orientation]), 'hint' or 'quit': ");
+            }
+            io::stdout().flush().ok();
+
+            let mut input = String::new();
+            if let Err(e) = io::stdin().read_line(&mut input) {
+                println!("Error reading input: {}", e);
+                return None;
+            }
+            if let Some(command) = GameCommand::parse(&input) {
+                self.pending_command = Some(command);
+                return None;
+            }
+
+            match input.trim().to_lowercase().as_str() {
+                "quit" | "q" | "exit" => {
+                    println!("Thanks for playing!");
+                    return None;
+                }
+                "hint" => {
+                    match self.hint_ai.choose_hand_move(game) {
+                        Some(hint) => println!("💡 Hint from {}: {} {} {} {}",
+                                              self.hint_ai.get_name(), hint.piece_index + 1, hint.y, hint.x, hint.orientation),
+                        None => println!("No valid moves available!"),
+                    }
+                }
+                other => match self.parse_hand_move(other) {
+                    Some(hand_move) if game.is_valid_hand_move(hand_move) => return Some(hand_move),
+                    Some(_) => println!("❌ Invalid move! Check the rules and try again."),
+                    None => println!("❌ Invalid input format! Use: piece row column (e.g., '2 5 3')"),
+                },
+            }
+        }
+    }
+
+    fn get_oriented_move(&mut self, game: &GameState, piece: &Piece) -> Option<OrientedMove> {
+        if game.piece_rules.is_fixed() {
+            return self.get_move(game, piece).map(|(x, y)| OrientedMove { orientation: 0, x, y });
+        }
+
+        loop {
+            self.display_game_info(game, piece);
+            println!("\nAllowed orientations:");
+            for orientation in game.piece_rules.orientations() {
+                println!("Orientation {}:", orientation);
+                print!("{}", piece.oriented(orientation).display());
+            }
+            print!("\nEnter move (row column [orientation]), 'hint' or 'quit': ");
+            io::stdout().flush().ok();
+
+            let mut input = String::new();
+            if let Err(e) = io::stdin().read_line(&mut input) {
+                println!("Error reading input: {}", e);
+                return None;
+            }
+            if let Some(command) = GameCommand::parse(&input) {
+                self.pending_command = Some(command);
+                return None;
+            }
+
+            match input.trim().to_lowercase().as_str() {
+                "quit" | "q" | "exit" => {
+                    println!("Thanks for playing!");
+                    return None;
+                }
+                "hint" => {
+                    match self.hint_ai.choose_oriented_move(game, piece) {
+                        Some(hint) => println!("💡 Hint from {}: {} {} {}",
+                                              self.hint_ai.get_name(), hint.y, hint.x, hint.orientation),
+                        None => println!("No valid moves available!"),
+                    }
+                }
+                other => match self.parse_oriented_move(other) {
+                    Some(oriented_move) if game.is_valid_oriented_move(piece, oriented_move) => return Some(oriented_move),
+                    Some(_) => println!("❌ Invalid move! Check the rules and try again."),
+                    None => println!("❌ Invalid input format! Use: row column [orientation] (e.g., '5 3 1')"),
+                },
+            }
+        }
+    }
+
     fn get_name(&self) -> &str {
         &self.name
     }
@@ -219,6 +360,10 @@ impl Player for HumanPlayer {
     fn is_human(&self) -> bool {
         true
     }
+
+    fn take_command(&mut self) -> Option<GameCommand> {
+        self.pending_command.take()
+    }
 }
 
 pub struct AIPlayerWrapper {
@@ -252,6 +397,33 @@ impl Player for AIPlayerWrapper {
         }
     }
 
+    fn get_hand_move(&mut self, game: &GameState) -> Option<HandMove> {
+        println!("\n{} is thinking...", self.ai.get_name());
+
+        let result = self.ai.choose_hand_move(game);
+        match result {
+            Some(hand_move) => println!("🤖 {} chose piece {} at ({}, {})",
+                                        self.ai.get_name(), hand_move.piece_index + 1, hand_move.y, hand_move.x),
+            None => println!("🤖 {} found no valid moves", self.ai.get_name()),
+        }
+        result
+    }
+
+    fn get_oriented_move(&mut self, game: &GameState, piece: &Piece) -> Option<OrientedMove> {
+        if game.piece_rules.is_fixed() {
+            return self.get_move(game, piece).map(|(x, y)| OrientedMove { orientation: 0, x, y });
+        }
+
+        println!("\n{} is thinking...", self.ai.get_name());
+        let result = self.ai.choose_oriented_move(game, piece);
+        match result {
+            Some(oriented_move) => println!("🤖 {} chose: ({}, {}) in orientation {}",
+                                            self.ai.get_name(), oriented_move.y, oriented_move.x, oriented_move.orientation),
+            None => println!("🤖 {} found no valid moves", self.ai.get_name()),
+        }
+        result
+    }
+
     fn get_name(&self) -> &str {
         self.ai.get_name()
     }
@@ -260,3 +432,14 @@ impl Player for AIPlayerWrapper {
         false
     }
 }
+
+/// "Player 1: 12 | Player 2: 9 | ..." for every player in the game.
+fn score_line(game: &GameState) -> String {
+    let scores: Vec<String> = game
+        .player_scores()
+        .iter()
+        .enumerate()
+        .map(|(index, score)| format!("Player {}: {}", index + 1, score))
+        .collect();
+    scores.join(" | ")
+}
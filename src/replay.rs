@@ -0,0 +1,293 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{GameState, Piece, Topology};
+
+/// One turn of a replay: the piece as it was placed, after any rotation, and
+/// where. `position` is `None` when the player could not place the piece
+/// dealt to them and dropped out.
+#[derive(Debug, Clone)]
+pub struct ReplayMove {
+    pub player: u8,
+    pub piece: Piece,
+    pub position: Option<(usize, usize)>,
+}
+
+/// A finished game as a starting position and a list of moves. Intermediate
+/// positions are not stored; `states` rebuilds them by playing the moves.
+///
+/// The file format is `name = value` header lines and `#` comments like
+/// saved games, then a `[map]` section with the starting board in the map
+/// format and a `[moves]` section with one move per line: `<player> <x> <y>
+/// <piece>`, or `<player> - <piece>` for a player dropping out, where the
+/// piece is written as a `to_pattern` string.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// The board, rules and players when the first move was made.
+    pub initial: GameState,
+    /// Master seed the game was played with.
+    pub seed: u64,
+    /// How each seat was played, e.g. `human`, `ai hard` or `bot <command>`.
+    pub players: Vec<String>,
+    pub moves: Vec<ReplayMove>,
+}
+
+impl Replay {
+    pub const VERSION: u32 = 1;
+
+    /// Starts recording a game from `initial`. Hands and previewed pieces
+    /// are not kept: the moves say which piece was played.
+    pub fn new(initial: &GameState, seed: u64, players: Vec<String>) -> Self {
+        let mut initial = initial.clone();
+        initial.hands.clear();
+        initial.upcoming.clear();
+        Self {
+            initial,
+            seed,
+            players,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Records `player` placing `piece` (as placed) at `(x, y)`.
+    pub fn record_move(&mut self, player: u8, piece: &Piece, x: usize, y: usize) {
+        self.moves.push(ReplayMove {
+            player,
+            piece: piece.clone(),
+            position: Some((x, y)),
+        });
+    }
+
+    /// Records every player eliminated in `game` since `eliminated_before`
+    /// was taken, as unable to place `piece`.
+    pub fn record_dropouts(&mut self, eliminated_before: &[bool], game: &GameState, piece: &Piece) {
+        for (index, &out) in game.eliminated.iter().enumerate() {
+            if out && !eliminated_before.get(index).copied().unwrap_or(false) {
+                self.moves.push(ReplayMove {
+                    player: index as u8 + 1,
+                    piece: piece.clone(),
+                    position: None,
+                });
+            }
+        }
+    }
+
+    /// Every position of the game: the starting one, then one after each
+    /// move. The last one is scored with `end_game`. Fails if a move is not
+    /// legal in the position it was recorded in.
+    pub fn states(&self) -> io::Result<Vec<GameState>> {
+        let mut game = self.initial.clone();
+        if let Some(first) = self.moves.first() {
+            game.current_player = first.player;
+        }
+        let mut states = Vec::with_capacity(self.moves.len() + 1);
+        states.push(game.clone());
+
+        for (index, replay_move) in self.moves.iter().enumerate() {
+            let player = replay_move.player;
+            if player == 0 || player > game.num_players {
+                return Err(invalid(format!("move {}: no player {}", index + 1, player)));
+            }
+            game.current_player = player;
+            match replay_move.position {
+                Some((x, y)) => {
+                    if !game.place_piece(&replay_move.piece, x, y) {
+                        return Err(invalid(format!("move {}: player {} cannot place the piece at ({}, {})", index + 1, player, x, y)));
+                    }
+                }
+                None => game.eliminated[player as usize - 1] = true,
+            }
+            game.turn += 1;
+            if let Some(next) = self.moves.get(index + 1) {
+                game.current_player = next.player;
+            }
+            states.push(game.clone());
+        }
+
+        if let Some(last) = states.last_mut() {
+            last.end_game();
+        }
+        Ok(states)
+    }
+
+    /// The position after the last move.
+    pub fn final_state(&self) -> io::Result<GameState> {
+        let mut states = self.states()?;
+        Ok(states.pop().unwrap_or_else(|| self.initial.clone()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_replay_string())
+    }
+
+    /// Saves the replay under a new name in `directory`, created if needed,
+    /// and returns the file's path.
+    pub fn save_in<P: AsRef<Path>>(&self, directory: P) -> io::Result<PathBuf> {
+        fs::create_dir_all(&directory)?;
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let mut path = directory.as_ref().join(format!("game_{}_{}.replay", time, self.seed));
+        let mut copy = 1;
+        while path.exists() {
+            copy += 1;
+            path = directory.as_ref().join(format!("game_{}_{}_{}.replay", time, self.seed, copy));
+        }
+        self.save(&path)?;
+        Ok(path)
+    }
+
+    pub fn to_replay_string(&self) -> String {
+        let game = &self.initial;
+        let mut text = String::from("# Filler replay\n");
+        text.push_str(&format!("version = {}\n", Self::VERSION));
+        text.push_str(&format!("seed = {}\n", self.seed));
+        text.push_str(&format!("turn = {}\n", game.turn));
+        text.push_str(&format!("rotate = {}\n", game.piece_rules.rotate));
+        text.push_str(&format!("mirror = {}\n", game.piece_rules.mirror));
+        let topology = match game.topology {
+            Topology::Bounded => "bounded",
+            Topology::Torus => "torus",
+        };
+        text.push_str(&format!("topology = {}\n", topology));
+        let eliminated: Vec<String> = (1..=game.num_players)
+            .filter(|&p| game.is_eliminated(p))
+            .map(|p| p.to_string())
+            .collect();
+        if !eliminated.is_empty() {
+            text.push_str(&format!("eliminated = {}\n", eliminated.join(" ")));
+        }
+        for (index, player) in self.players.iter().enumerate() {
+            text.push_str(&format!("player{} = {}\n", index + 1, player));
+        }
+        text.push_str("[map]\n");
+        text.push_str(&game.to_map_string());
+        text.push_str("[moves]\n");
+        for replay_move in &self.moves {
+            match replay_move.position {
+                Some((x, y)) => text.push_str(&format!("{} {} {} {}\n", replay_move.player, x, y, replay_move.piece.to_pattern())),
+                None => text.push_str(&format!("{} - {}\n", replay_move.player, replay_move.piece.to_pattern())),
+            }
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let lines: Vec<&str> = text.lines().collect();
+        let map_start = lines
+            .iter()
+            .position(|line| line.trim() == "[map]")
+            .ok_or_else(|| invalid("missing [map] section".to_string()))?;
+        let moves_start = lines
+            .iter()
+            .position(|line| line.trim() == "[moves]")
+            .filter(|&start| start > map_start)
+            .ok_or_else(|| invalid("missing [moves] section after [map]".to_string()))?;
+
+        let mut initial = GameState::from_map_str(&lines[map_start + 1..moves_start].join("\n"))
+            .map_err(|err| invalid(format!("map after line {}: {}", map_start + 1, err)))?;
+        let mut replay = Replay {
+            initial: GameState::new(1, 1),
+            seed: 0,
+            players: Vec::new(),
+            moves: Vec::new(),
+        };
+        let mut version = None;
+        let mut seats: Vec<(usize, String)> = Vec::new();
+
+        for (line_no, raw_line) in lines[..map_start].iter().enumerate() {
+            let line_no = line_no + 1;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("line {}: expected name = value", line_no)))?;
+            let (name, value) = (name.trim(), value.trim());
+            let number = |value: &str| -> io::Result<u64> {
+                value
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: invalid value for '{}'", line_no, name)))
+            };
+            let flag = |value: &str| -> io::Result<bool> {
+                value
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: expected true or false for '{}'", line_no, name)))
+            };
+            match name {
+                "version" => version = Some(number(value)?),
+                "seed" => replay.seed = number(value)?,
+                "turn" => initial.turn = number(value)? as u32,
+                "rotate" => initial.piece_rules.rotate = flag(value)?,
+                "mirror" => initial.piece_rules.mirror = flag(value)?,
+                "topology" => {
+                    initial.topology = match value {
+                        "bounded" => Topology::Bounded,
+                        "torus" => Topology::Torus,
+                        _ => return Err(invalid(format!("line {}: unknown topology '{}'", line_no, value))),
+                    }
+                }
+                "eliminated" => {
+                    for part in value.split_whitespace() {
+                        let player = number(part)? as usize;
+                        match initial.eliminated.get_mut(player.wrapping_sub(1)) {
+                            Some(out) => *out = true,
+                            None => return Err(invalid(format!("line {}: no player {}", line_no, player))),
+                        }
+                    }
+                }
+                _ => match name.strip_prefix("player").and_then(|n| n.parse::<usize>().ok()) {
+                    Some(seat) if seat == 0 || seat > initial.num_players as usize => {
+                        return Err(invalid(format!("line {}: no player {}", line_no, seat)));
+                    }
+                    Some(seat) => seats.push((seat, value.to_string())),
+                    None => return Err(invalid(format!("line {}: unknown setting '{}'", line_no, name))),
+                },
+            }
+        }
+
+        if version != Some(Self::VERSION as u64) {
+            return Err(invalid(format!("unsupported replay version, expected version = {}", Self::VERSION)));
+        }
+        seats.sort_by_key(|(seat, _)| *seat);
+        replay.players = seats.into_iter().map(|(_, player)| player).collect();
+
+        for (offset, raw_line) in lines[moves_start + 1..].iter().enumerate() {
+            let line_no = moves_start + offset + 2;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            replay.moves.push(parse_move(line).map_err(|err| invalid(format!("line {}: {}", line_no, err)))?);
+        }
+
+        replay.initial = initial;
+        Ok(replay)
+    }
+}
+
+fn parse_move(line: &str) -> io::Result<ReplayMove> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let number = |part: &str| -> io::Result<usize> {
+        part.parse().map_err(|_| invalid(format!("invalid number '{}'", part)))
+    };
+    let (player, position, pattern) = match parts.as_slice() {
+        [player, "-", pattern] => (player, None, pattern),
+        [player, x, y, pattern] => (player, Some((number(x)?, number(y)?)), pattern),
+        _ => return Err(invalid("expected '<player> <x> <y> <piece>' or '<player> - <piece>'".to_string())),
+    };
+    Ok(ReplayMove {
+        player: player.parse().map_err(|_| invalid(format!("invalid player '{}'", player)))?,
+        piece: Piece::from_pattern(pattern)?,
+        position,
+    })
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
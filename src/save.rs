@@ -0,0 +1,320 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::{derive_seed, GameState, Piece, PieceGenerator, PieceSet, SeedStream, Topology};
+
+/// A request typed at a human's move prompt instead of a move.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameCommand {
+    Save(String),
+    Load(String),
+}
+
+impl GameCommand {
+    /// Parses `save <file>` or `load <file>`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let (command, path) = input.trim().split_once(char::is_whitespace)?;
+        let path = path.trim().to_string();
+        match command.to_lowercase().as_str() {
+            "save" => Some(GameCommand::Save(path)),
+            "load" => Some(GameCommand::Load(path)),
+            _ => None,
+        }
+    }
+}
+
+/// A game in progress, with everything needed to continue it exactly.
+///
+/// The file format is `name = value` lines and `#` comments, like weights
+/// files, followed by a `[board]` line and the board in the map format.
+/// Pieces are not stored: the generator is rebuilt from the master seed and
+/// advanced past the pieces already dealt. Hands are stored piece by piece
+/// as `to_pattern` strings.
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    pub game: GameState,
+    /// Master seed the game was started with.
+    pub seed: u64,
+    /// Pieces the generator dealt before the piece to be played next.
+    pub pieces_dealt: usize,
+    /// A piece-set preset name or the path of a piece-set file.
+    pub piece_set: String,
+    pub preview: usize,
+    /// How each seat is played, e.g. `human`, `ai hard` or `bot <command>`.
+    pub players: Vec<String>,
+}
+
+impl SavedGame {
+    pub const VERSION: u32 = 1;
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_save_string())
+    }
+
+    /// The piece set named by `piece_set`.
+    pub fn load_piece_set(&self) -> io::Result<PieceSet> {
+        load_piece_set(&self.piece_set)
+    }
+
+    /// A generator that deals the rest of the saved game's pieces.
+    pub fn piece_generator(&self) -> io::Result<PieceGenerator> {
+        let mut generator = PieceGenerator::with_set(derive_seed(self.seed, SeedStream::Pieces), self.load_piece_set()?);
+        generator.skip(self.pieces_dealt);
+        Ok(generator)
+    }
+
+    pub fn to_save_string(&self) -> String {
+        let game = &self.game;
+        let mut text = String::from("# Filler saved game\n");
+        text.push_str(&format!("version = {}\n", Self::VERSION));
+        text.push_str(&format!("seed = {}\n", self.seed));
+        text.push_str(&format!("pieces_dealt = {}\n", self.pieces_dealt));
+        text.push_str(&format!("piece_set = {}\n", self.piece_set));
+        text.push_str(&format!("preview = {}\n", self.preview));
+        text.push_str(&format!("players = {}\n", game.num_players));
+        text.push_str(&format!("current_player = {}\n", game.current_player));
+        text.push_str(&format!("turn = {}\n", game.turn));
+        text.push_str(&format!("rotate = {}\n", game.piece_rules.rotate));
+        text.push_str(&format!("mirror = {}\n", game.piece_rules.mirror));
+        let topology = match game.topology {
+            Topology::Bounded => "bounded",
+            Topology::Torus => "torus",
+        };
+        text.push_str(&format!("topology = {}\n", topology));
+        if game.is_team_game() {
+            text.push_str(&format!("teams = {}\n", join(&game.teams)));
+        }
+        let eliminated: Vec<u8> = (1..=game.num_players).filter(|&p| game.is_eliminated(p)).collect();
+        if !eliminated.is_empty() {
+            text.push_str(&format!("eliminated = {}\n", join(&eliminated)));
+        }
+        for (index, hand) in game.hands.iter().enumerate() {
+            let pieces: Vec<String> = hand.iter().map(Piece::to_pattern).collect();
+            text.push_str(&format!("hand{} = {}\n", index + 1, pieces.join(" ")));
+        }
+        for (index, player) in self.players.iter().enumerate() {
+            text.push_str(&format!("player{} = {}\n", index + 1, player));
+        }
+        text.push_str("[board]\n");
+        for row in &game.board {
+            text.extend(row.iter().map(|cell| cell.to_char()));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut values: Vec<(usize, &str, &str)> = Vec::new();
+        let mut board_text = None;
+
+        for (line_no, raw_line) in text.lines().enumerate() {
+            if raw_line.trim() == "[board]" {
+                let rest: Vec<&str> = text.lines().skip(line_no + 1).collect();
+                board_text = Some((line_no + 1, rest.join("\n")));
+                break;
+            }
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("line {}: expected name = value", line_no + 1)))?;
+            values.push((line_no + 1, name.trim(), value.trim()));
+        }
+
+        let (board_line, board_text) = board_text.ok_or_else(|| invalid("missing [board] section".to_string()))?;
+        let mut game = GameState::from_map_str(&board_text)
+            .map_err(|err| invalid(format!("board after line {}: {}", board_line, err)))?;
+
+        let mut saved = SavedGame {
+            game: GameState::new(1, 1),
+            seed: 0,
+            pieces_dealt: 0,
+            piece_set: "classic".to_string(),
+            preview: 0,
+            players: Vec::new(),
+        };
+        let mut version = None;
+        let mut players = None;
+        let mut hands: Vec<(usize, Vec<Piece>)> = Vec::new();
+        let mut seats: Vec<(usize, String)> = Vec::new();
+
+        for (line_no, name, value) in values {
+            let number = |value: &str| -> io::Result<u64> {
+                value
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: invalid value for '{}'", line_no, name)))
+            };
+            let flag = |value: &str| -> io::Result<bool> {
+                value
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: expected true or false for '{}'", line_no, name)))
+            };
+            match name {
+                "version" => version = Some(number(value)?),
+                "seed" => saved.seed = number(value)?,
+                "pieces_dealt" => saved.pieces_dealt = number(value)? as usize,
+                "piece_set" => saved.piece_set = value.to_string(),
+                "preview" => saved.preview = number(value)? as usize,
+                "players" => players = Some(number(value)?),
+                "current_player" => game.current_player = number(value)? as u8,
+                "turn" => game.turn = number(value)? as u32,
+                "rotate" => game.piece_rules.rotate = flag(value)?,
+                "mirror" => game.piece_rules.mirror = flag(value)?,
+                "topology" => {
+                    game.topology = match value {
+                        "bounded" => Topology::Bounded,
+                        "torus" => Topology::Torus,
+                        _ => return Err(invalid(format!("line {}: unknown topology '{}'", line_no, value))),
+                    }
+                }
+                "teams" => game.set_teams(parse_list(value, line_no)?)?,
+                "eliminated" => {
+                    for player in parse_list(value, line_no)? {
+                        match game.eliminated.get_mut((player as usize).wrapping_sub(1)) {
+                            Some(out) => *out = true,
+                            None => return Err(invalid(format!("line {}: no player {}", line_no, player))),
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(seat) = name.strip_prefix("hand").and_then(|n| n.parse::<usize>().ok()) {
+                        let pieces = value
+                            .split_whitespace()
+                            .map(Piece::from_pattern)
+                            .collect::<io::Result<Vec<Piece>>>()
+                            .map_err(|err| invalid(format!("line {}: {}", line_no, err)))?;
+                        hands.push((seat, pieces));
+                    } else if let Some(seat) = name.strip_prefix("player").and_then(|n| n.parse::<usize>().ok()) {
+                        if seat == 0 || seat > game.num_players as usize {
+                            return Err(invalid(format!("line {}: no player {}", line_no, seat)));
+                        }
+                        seats.push((seat, value.to_string()));
+                    } else {
+                        return Err(invalid(format!("line {}: unknown setting '{}'", line_no, name)));
+                    }
+                }
+            }
+        }
+
+        if version != Some(Self::VERSION as u64) {
+            return Err(invalid(format!("unsupported save version, expected version = {}", Self::VERSION)));
+        }
+        if players.is_some_and(|players| players != game.num_players as u64) {
+            return Err(invalid("players does not match the start cells on the board".to_string()));
+        }
+        if game.current_player == 0 || game.current_player > game.num_players {
+            return Err(invalid(format!("current_player {} is not in the game", game.current_player)));
+        }
+
+        hands.sort_by_key(|(seat, _)| *seat);
+        if !hands.is_empty() {
+            if hands.iter().enumerate().any(|(index, (seat, _))| *seat != index + 1) || hands.len() != game.num_players as usize {
+                return Err(invalid("every player needs exactly one hand".to_string()));
+            }
+            game.hands = hands.into_iter().map(|(_, pieces)| pieces).collect();
+        }
+        seats.sort_by_key(|(seat, _)| *seat);
+        saved.players = seats.into_iter().map(|(_, player)| player).collect();
+
+        saved.load_piece_set()?;
+        saved.game = game;
+        Ok(saved)
+    }
+}
+
+/// What a saved game records beyond the board, kept by a running game for
+/// the `save` and `load` commands typed at a human's prompt.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub seed: u64,
+    /// A piece-set preset name or the path of a piece-set file.
+    pub piece_set: String,
+    pub preview: usize,
+    pub seats: Vec<String>,
+}
+
+impl Session {
+    /// Carries out a `save` or `load` typed at the prompt. `pieces_dealt`
+    /// leaves out a piece that was dealt but not played yet. Only games with
+    /// as many players as `game` can be loaded. Returns true if another game
+    /// was loaded.
+    pub fn run(
+        &mut self,
+        command: GameCommand,
+        game: &mut GameState,
+        piece_generator: &mut PieceGenerator,
+        pieces_dealt: usize,
+    ) -> bool {
+        match command {
+            GameCommand::Save(path) => {
+                let saved = SavedGame {
+                    game: game.clone(),
+                    seed: self.seed,
+                    pieces_dealt,
+                    piece_set: self.piece_set.clone(),
+                    preview: self.preview,
+                    players: self.seats.clone(),
+                };
+                match saved.save(&path) {
+                    Ok(()) => println!("Game saved to {}", path),
+                    Err(err) => println!("Could not save to {}: {}", path, err),
+                }
+                false
+            }
+            GameCommand::Load(path) => {
+                let loaded = SavedGame::load(&path)
+                    .and_then(|saved| saved.piece_generator().map(|generator| (saved, generator)));
+                match loaded {
+                    Ok((saved, _)) if saved.game.num_players != game.num_players => {
+                        println!("Could not load {}: it has {} players, not {}", path, saved.game.num_players, game.num_players);
+                        false
+                    }
+                    Ok((saved, generator)) => {
+                        println!("Loaded {} at turn {}", path, saved.game.turn);
+                        *game = saved.game;
+                        *piece_generator = generator;
+                        self.seed = saved.seed;
+                        self.piece_set = saved.piece_set;
+                        self.preview = saved.preview;
+                        true
+                    }
+                    Err(err) => {
+                        println!("Could not load {}: {}", path, err);
+                        false
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A piece-set preset by name, or else a piece-set file.
+pub fn load_piece_set(name: &str) -> io::Result<PieceSet> {
+    match PieceSet::preset(name) {
+        Some(set) => Ok(set),
+        None => PieceSet::load(name),
+    }
+}
+
+fn join(values: &[u8]) -> String {
+    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+    values.join(" ")
+}
+
+fn parse_list(value: &str, line_no: usize) -> io::Result<Vec<u8>> {
+    value
+        .split_whitespace()
+        .map(|part| part.parse().map_err(|_| invalid(format!("line {}: invalid number '{}'", line_no, part))))
+        .collect()
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
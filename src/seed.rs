@@ -0,0 +1,36 @@
+/// Independent random streams derived from a game's master seed.
+///
+/// Recording the master seed is enough to replay a game exactly: the piece
+/// sequence and every AI's random choices are all derived from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeedStream {
+    Pieces,
+    Player(u8),
+    Visualizer,
+}
+
+impl SeedStream {
+    fn id(&self) -> u64 {
+        match self {
+            SeedStream::Pieces => 0,
+            SeedStream::Visualizer => 1,
+            SeedStream::Player(player) => 16 + *player as u64,
+        }
+    }
+}
+
+/// Seed for one stream of a game played with `master`.
+pub fn derive_seed(master: u64, stream: SeedStream) -> u64 {
+    // SplitMix64, so neighbouring master seeds give unrelated streams
+    let mut z = master
+        .wrapping_add(stream.id().wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A fresh master seed from the operating system's entropy source.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
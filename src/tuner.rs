@@ -0,0 +1,257 @@
+use std::io::{self, Write};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::{create_ai_with_options, derive_seed, play_headless_game, AIDifficulty, AIOptions, EvalWeights, GameState, SeedStream};
+
+/// Settings for an SPSA run over the evaluation weights.
+#[derive(Debug, Clone)]
+pub struct TunerConfig {
+    pub difficulty: AIDifficulty,
+    /// Names of the `EvalWeights` fields being tuned.
+    pub parameters: Vec<String>,
+    pub maps: Vec<GameState>,
+    pub piece_seeds: Vec<u64>,
+    pub generations: u32,
+    /// SPSA step size `a`, relative to each weight's starting magnitude.
+    pub step_size: f64,
+    /// SPSA perturbation size `c`, relative to each weight's starting magnitude.
+    pub perturbation: f64,
+    pub rng_seed: u64,
+}
+
+impl TunerConfig {
+    /// Tunes the weights that `difficulty` actually reads. Easy plays
+    /// random moves and reads none, so it cannot be tuned.
+    pub fn new(difficulty: AIDifficulty, maps: Vec<GameState>) -> io::Result<Self> {
+        let parameters = match difficulty {
+            AIDifficulty::Hard | AIDifficulty::Expert => vec!["score_diff", "center_control", "compactness"],
+            AIDifficulty::Medium => vec!["piece_size", "center_distance", "blocking"],
+            AIDifficulty::Easy => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "easy plays random moves and reads no weights; tune medium, hard or expert",
+                ));
+            }
+        };
+
+        Ok(Self {
+            difficulty,
+            parameters: parameters.into_iter().map(String::from).collect(),
+            maps,
+            piece_seeds: vec![1, 2, 3, 4],
+            generations: 20,
+            step_size: 0.5,
+            perturbation: 0.2,
+            rng_seed: 0,
+        })
+    }
+}
+
+/// Outcome of a batch of games, counted from the first weight set's side.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    /// Sum over games of `(own - opponent) / (own + opponent)` territory.
+    pub territory_margin: f64,
+}
+
+impl MatchResult {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// Average of win balance and territory margin, in `[-1, 1]`.
+    ///
+    /// Seat advantage decides many games outright, so the territory margin
+    /// keeps the signal informative when wins and losses cancel out.
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        let win_balance = self.wins as f64 - self.losses as f64;
+        (win_balance + self.territory_margin) / (2.0 * self.games() as f64)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerationLog {
+    pub generation: u32,
+    pub plus: EvalWeights,
+    pub minus: EvalWeights,
+    pub result: MatchResult,
+    pub weights: EvalWeights,
+}
+
+impl GenerationLog {
+    pub fn to_log_line(&self) -> String {
+        let mut line = format!(
+            "gen {:3}: plus {}-{}-{} vs minus ({:+.2}) ->",
+            self.generation, self.result.wins, self.result.losses, self.result.draws, self.result.score()
+        );
+        for name in EvalWeights::NAMES {
+            line.push_str(&format!(" {}={}", name, self.weights.get(name).unwrap_or(0)));
+        }
+        line
+    }
+}
+
+/// Plays every map and seed twice, swapping seats, and reports the result
+/// for `first`.
+pub fn play_match(
+    difficulty: &AIDifficulty,
+    first: &EvalWeights,
+    second: &EvalWeights,
+    maps: &[GameState],
+    piece_seeds: &[u64],
+) -> MatchResult {
+    let mut result = MatchResult::default();
+
+    for map in maps {
+        for &seed in piece_seeds {
+            for first_starts in [true, false] {
+                // Seeded by seat, so both weight sets face the same random choices
+                let (first_seat, second_seat) = if first_starts { (1, 2) } else { (2, 1) };
+                let options = |weights: &EvalWeights, seat: u8| AIOptions {
+                    weights: weights.clone(),
+                    seed: Some(derive_seed(seed, SeedStream::Player(seat))),
+                    ..AIOptions::default()
+                };
+                let mut ai_first = create_ai_with_options(difficulty.clone(), &options(first, first_seat));
+                let mut ai_second = create_ai_with_options(difficulty.clone(), &options(second, second_seat));
+
+                let final_state = if first_starts {
+                    play_headless_game(map, ai_first.as_mut(), ai_second.as_mut(), seed)
+                } else {
+                    play_headless_game(map, ai_second.as_mut(), ai_first.as_mut(), seed)
+                };
+
+                let (p1_score, p2_score) = final_state.calculate_scores();
+                let (own, opponent) = if first_starts {
+                    (p1_score as f64, p2_score as f64)
+                } else {
+                    (p2_score as f64, p1_score as f64)
+                };
+                result.territory_margin += (own - opponent) / (own + opponent).max(1.0);
+
+                match final_state.winner {
+                    Some(winner) if winner == first_seat => result.wins += 1,
+                    Some(_) => result.losses += 1,
+                    None => result.draws += 1,
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Simultaneous-perturbation stochastic approximation over integer weights.
+///
+/// Each generation perturbs every tuned weight by ±c in a random direction,
+/// plays the two perturbed sets against each other and moves the current
+/// weights towards the winner.
+pub struct Tuner {
+    config: TunerConfig,
+    theta: Vec<f64>,
+    scale: Vec<f64>,
+    base: EvalWeights,
+    rng: StdRng,
+    generation: u32,
+    history: Vec<GenerationLog>,
+}
+
+impl Tuner {
+    pub fn new(config: TunerConfig, initial: EvalWeights) -> Self {
+        let theta: Vec<f64> = config
+            .parameters
+            .iter()
+            .map(|name| initial.get(name).unwrap_or(0) as f64)
+            .collect();
+        let scale = theta.iter().map(|value| value.abs().max(1.0)).collect();
+        let rng = StdRng::seed_from_u64(config.rng_seed);
+
+        Self {
+            config,
+            theta,
+            scale,
+            base: initial,
+            rng,
+            generation: 0,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn weights(&self) -> EvalWeights {
+        self.weights_at(&self.theta)
+    }
+
+    pub fn history(&self) -> &[GenerationLog] {
+        &self.history
+    }
+
+    fn weights_at(&self, theta: &[f64]) -> EvalWeights {
+        let mut weights = self.base.clone();
+        for (name, value) in self.config.parameters.iter().zip(theta) {
+            weights.set(name, value.round() as i32);
+        }
+        weights
+    }
+
+    pub fn run_generation(&mut self) -> &GenerationLog {
+        let k = self.generation as f64 + 1.0;
+        let a_k = self.config.step_size / k.powf(0.602);
+        let c_k = self.config.perturbation / k.powf(0.101);
+
+        let delta: Vec<f64> = (0..self.theta.len())
+            .map(|_| if self.rng.gen::<bool>() { 1.0 } else { -1.0 })
+            .collect();
+
+        // Perturb by at least one unit so the two sides always differ
+        let offsets: Vec<f64> = self
+            .scale
+            .iter()
+            .zip(&delta)
+            .map(|(scale, d)| (c_k * scale).round().max(1.0) * d)
+            .collect();
+        let theta_plus: Vec<f64> = self.theta.iter().zip(&offsets).map(|(t, o)| t + o).collect();
+        let theta_minus: Vec<f64> = self.theta.iter().zip(&offsets).map(|(t, o)| t - o).collect();
+
+        let plus = self.weights_at(&theta_plus);
+        let minus = self.weights_at(&theta_minus);
+        let result = play_match(
+            &self.config.difficulty,
+            &plus,
+            &minus,
+            &self.config.maps,
+            &self.config.piece_seeds,
+        );
+
+        let gradient_sign = result.score();
+        for ((theta, scale), d) in self.theta.iter_mut().zip(&self.scale).zip(&delta) {
+            *theta += scale * a_k * gradient_sign * d / (2.0 * c_k);
+        }
+
+        self.generation += 1;
+        let log = GenerationLog {
+            generation: self.generation,
+            plus,
+            minus,
+            result,
+            weights: self.weights(),
+        };
+        self.history.push(log);
+        self.history.last().expect("generation just recorded")
+    }
+
+    /// Runs all configured generations, writing one log line per generation.
+    pub fn run(&mut self, log: &mut dyn Write) -> io::Result<EvalWeights> {
+        for _ in 0..self.config.generations {
+            let line = self.run_generation().to_log_line();
+            writeln!(log, "{}", line)?;
+            log.flush()?;
+        }
+        Ok(self.weights())
+    }
+}
@@ -1,5 +1,5 @@
 use std::io::{self, Write};
-use crate::{GameState, Player, HumanPlayer, AIPlayerWrapper, AIDifficulty, create_ai, GameVisualizer, GameReplay};
+use crate::{AIPlayer, GameState, PieceGenerator, Player, HumanPlayer, AIPlayerWrapper, AIDifficulty, create_ai_with_options, available_threads, AIOptions, GameVisualizer, Replay, WeightsConfig, derive_seed, SeedStream, PieceRules, Topology, Session, Piece};
 
 #[derive(Debug, Clone)]
 pub enum GameMode {
@@ -16,7 +16,23 @@ pub struct GameConfig {
     pub show_animations: bool,
     pub player1_name: String,
     pub player2_name: String,
+    /// Whether to offer watching the replay when the game is over.
     pub enable_replay: bool,
+    /// Directory a replay file is written to after every game, or `None`
+    /// to keep no replays.
+    pub replay_dir: Option<String>,
+    pub weights: WeightsConfig,
+    pub ai_threads: usize,
+    /// Master seed for the pieces and every AI's random choices.
+    pub seed: u64,
+    /// Upcoming pieces shown to both players (0 for the standard game).
+    pub preview: usize,
+    /// Pieces each player holds and chooses from (0 for the standard game).
+    pub hand_size: usize,
+    /// Whether pieces may be rotated or mirrored.
+    pub piece_rules: PieceRules,
+    /// Whether the board's edges wrap around.
+    pub topology: Topology,
 }
 
 impl Default for GameConfig {
@@ -30,6 +46,14 @@ impl Default for GameConfig {
             player1_name: "Player 1".to_string(),
             player2_name: "AI".to_string(),
             enable_replay: false,
+            replay_dir: Some("replays".to_string()),
+            weights: WeightsConfig::default(),
+            ai_threads: available_threads(),
+            seed: 42,
+            preview: 0,
+            hand_size: 0,
+            piece_rules: PieceRules::default(),
+            topology: Topology::default(),
         }
     }
 }
@@ -186,7 +210,7 @@ pub fn setup_game() -> GameConfig {
     config.show_animations = matches!(input.trim().to_lowercase().as_str(), "y" | "yes");
 
     // Replay settings
-    print!("Enable game replay recording? (y/N): ");
+    print!("Watch the game replay afterwards? (y/N): ");
     io::stdout().flush().unwrap();
     let mut input = String::new();
     io::stdin().read_line(&mut input).unwrap();
@@ -215,47 +239,111 @@ pub fn setup_game() -> GameConfig {
 
 pub fn run_game(config: GameConfig) -> io::Result<()> {
     let mut game = GameState::new(config.board_width, config.board_height);
+    game.piece_rules = config.piece_rules;
+    game.topology = config.topology;
+    let ai_options = |player: u8| AIOptions {
+        weights: config.weights.for_board(config.board_width, config.board_height).clone(),
+        threads: config.ai_threads,
+        seed: Some(derive_seed(config.seed, SeedStream::Player(player))),
+        ..AIOptions::default()
+    };
     let visualizer = GameVisualizer::with_settings(
         config.show_animations,
         500,
         true,
-    );
+    )
+    .with_seed(derive_seed(config.seed, SeedStream::Visualizer));
     
-    let mut replay = if config.enable_replay {
-        Some(GameReplay::new())
-    } else {
-        None
-    };
-
     // Create players based on game mode
     let mut player1: Box<dyn Player> = match config.mode {
         GameMode::HumanVsAI | GameMode::HumanVsHuman => {
             Box::new(HumanPlayer::new(config.player1_name.clone()))
         }
         GameMode::AIVsAI => {
-            Box::new(AIPlayerWrapper::new(create_ai(config.ai_difficulty.clone())))
+            Box::new(AIPlayerWrapper::new(create_ai_with_options(config.ai_difficulty.clone(), &ai_options(1))))
         }
     };
 
     let mut player2: Box<dyn Player> = match config.mode {
         GameMode::HumanVsAI | GameMode::AIVsAI => {
-            Box::new(AIPlayerWrapper::new(create_ai(config.ai_difficulty.clone())))
+            Box::new(AIPlayerWrapper::new(create_ai_with_options(config.ai_difficulty.clone(), &ai_options(2))))
         }
         GameMode::HumanVsHuman => {
             Box::new(HumanPlayer::new(config.player2_name.clone()))
         }
     };
 
-    let mut piece_gen = crate::Piece::generate_sequence(42); // Fixed seed for reproducibility
+    let mut piece_gen = crate::Piece::generate_sequence(derive_seed(config.seed, SeedStream::Pieces));
+    let mut session = Session {
+        seed: config.seed,
+        piece_set: "classic".to_string(),
+        preview: config.preview,
+        seats: vec![seat_name(&config, player1.as_ref()), seat_name(&config, player2.as_ref())],
+    };
+    // A piece dealt but not played because a human saved or loaded
+    let mut pending_piece: Option<Piece> = None;
+    let mut replay = Replay::new(&game, config.seed, session.seats.clone());
     
     visualizer.display_game_header();
+    println!("Seed: {}", config.seed);
+    
+    if config.hand_size > 0 {
+        game.hands = (0..2)
+            .map(|_| (0..config.hand_size).map(|_| piece_gen.next()).collect())
+            .collect();
+    }
     
     // Main game loop
     loop {
-        let current_piece = piece_gen.next();
+        if game.uses_hands() {
+            if game.get_hand_moves().is_empty() {
+                game.end_game();
+                break;
+            }
+            print!("{}", game.display_board());
+
+            let current_player = if game.current_player == 1 {
+                &mut player1
+            } else {
+                &mut player2
+            };
+            let hand_move = current_player.get_hand_move(&game);
+            if let Some(command) = current_player.take_command() {
+                let dealt = piece_gen.dealt();
+                if session.run(command, &mut game, &mut piece_gen, dealt) {
+                    replay = Replay::new(&game, session.seed, session.seats.clone());
+                }
+                continue;
+            }
+            let Some(hand_move) = hand_move else {
+                visualizer.display_no_valid_moves(current_player.get_name());
+                break;
+            };
+            let Some(piece) = game.play_hand_move(hand_move) else {
+                println!("Invalid move! Try again.");
+                continue;
+            };
+            visualizer.display_move_result(&game, hand_move.x, hand_move.y, current_player.get_name(), true);
+            replay.record_move(game.current_player, &piece, hand_move.x, hand_move.y);
+
+            // Refill the hand the piece came from
+            game.hands[game.current_player as usize - 1].push(piece_gen.next());
+            game.switch_player();
+
+            if matches!(config.mode, GameMode::AIVsAI) && !current_player.is_human() {
+                std::thread::sleep(std::time::Duration::from_millis(1000));
+            }
+            continue;
+        }
+
+        let current_piece = pending_piece.take().unwrap_or_else(|| piece_gen.next());
+        game.upcoming = piece_gen.preview(session.preview);
         
         // Check if current player has valid moves
-        if game.check_game_over(&current_piece) {
+        let eliminated_before = game.eliminated.clone();
+        let game_over = game.check_game_over(&current_piece);
+        replay.record_dropouts(&eliminated_before, &game, &current_piece);
+        if game_over {
             break;
         }
 
@@ -269,19 +357,27 @@ pub fn run_game(config: GameConfig) -> io::Result<()> {
         };
 
         // Get move from player
-        let move_result = current_player.get_move(&game, &current_piece);
+        let move_result = current_player.get_oriented_move(&game, &current_piece);
+        if let Some(command) = current_player.take_command() {
+            // The current piece is dealt again when the game continues
+            let dealt = piece_gen.dealt() - 1;
+            if session.run(command, &mut game, &mut piece_gen, dealt) {
+                replay = Replay::new(&game, session.seed, session.seats.clone());
+            } else {
+                pending_piece = Some(current_piece);
+            }
+            continue;
+        }
         
         match move_result {
-            Some((x, y)) => {
-                let success = game.place_piece(&current_piece, x, y);
+            Some(oriented_move) => {
+                let (x, y) = (oriented_move.x, oriented_move.y);
+                let success = game.place_oriented(&current_piece, oriented_move);
                 visualizer.display_move_result(&game, x, y, current_player.get_name(), success);
                 
                 if success {
-                    // Record move for replay
-                    if let Some(ref mut replay) = replay {
-                        replay.add_move(game.current_player, current_piece.clone(), Some((x, y)), game.clone());
-                    }
-                    
+                    let placed = current_piece.oriented(oriented_move.orientation);
+                    replay.record_move(game.current_player, &placed, x, y);
                     game.switch_player();
                 } else {
                     println!("Invalid move! Try again.");
@@ -290,12 +386,6 @@ pub fn run_game(config: GameConfig) -> io::Result<()> {
             }
             None => {
                 visualizer.display_no_valid_moves(current_player.get_name());
-                
-                // Record no-move for replay
-                if let Some(ref mut replay) = replay {
-                    replay.add_move(game.current_player, current_piece.clone(), None, game.clone());
-                }
-                
                 game.switch_player();
             }
         }
@@ -309,21 +399,80 @@ pub fn run_game(config: GameConfig) -> io::Result<()> {
     // Display game over
     visualizer.display_game_over(&game, &config.player1_name, &config.player2_name);
 
+    if let Some(directory) = &config.replay_dir {
+        match replay.save_in(directory) {
+            Ok(path) => println!("Replay saved to {}", path.display()),
+            Err(e) => println!("Could not save the replay: {}", e),
+        }
+    }
+
     // Offer replay
-    if let Some(mut replay) = replay {
+    if config.enable_replay {
         print!("\nWould you like to watch the game replay? (y/N): ");
         io::stdout().flush()?;
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
         
         if matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
-            replay.play_replay(&visualizer);
+            visualizer.play_replay(&replay)?;
         }
     }
 
     Ok(())
 }
 
+/// How a seat is described in a saved game, e.g. `human Alice` or `ai hard`.
+fn seat_name(config: &GameConfig, player: &dyn Player) -> String {
+    if player.is_human() {
+        format!("human {}", player.get_name())
+    } else {
+        format!("ai {:?}", config.ai_difficulty).to_lowercase()
+    }
+}
+
+/// Plays a complete game between two AIs without any output and returns
+/// the final state. A player that returns no move or an invalid move ends
+/// the game, as in `filler_engine`.
+pub fn play_headless_game(
+    initial: &GameState,
+    player1: &mut dyn AIPlayer,
+    player2: &mut dyn AIPlayer,
+    piece_seed: u64,
+) -> GameState {
+    play_headless_match(initial, &mut [player1, player2], piece_seed)
+}
+
+/// Like `play_headless_game` for any number of players: `players[p - 1]`
+/// plays as player `p`, and there must be one AI per player of `initial`.
+pub fn play_headless_match(
+    initial: &GameState,
+    players: &mut [&mut dyn AIPlayer],
+    piece_seed: u64,
+) -> GameState {
+    assert_eq!(players.len(), initial.num_players as usize, "one AI per player");
+    let mut game = initial.clone();
+    let mut piece_gen = PieceGenerator::new(piece_seed);
+
+    loop {
+        let current_piece = piece_gen.next();
+        if game.check_game_over(&current_piece) {
+            break;
+        }
+
+        let move_result = players[game.current_player as usize - 1].choose_move(&game, &current_piece);
+
+        match move_result {
+            Some((x, y)) if game.place_piece(&current_piece, x, y) => game.switch_player(),
+            _ => {
+                game.end_game();
+                break;
+            }
+        }
+    }
+
+    game
+}
+
 pub fn display_main_menu() {
     println!("╔══════════════════════════════════════════════════════════════╗");
     println!("║                         FILLER GAME                          ║");
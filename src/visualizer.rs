@@ -1,11 +1,14 @@
 use std::io::{self, Write};
-use std::time::{Duration, Instant};
-use crate::{GameState, Piece};
+use std::time::Duration;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::{GameState, Piece, Replay, Topology};
 
 pub struct GameVisualizer {
     show_animations: bool,
     animation_delay: Duration,
     clear_screen: bool,
+    seed: u64,
 }
 
 impl GameVisualizer {
@@ -14,6 +17,7 @@ impl GameVisualizer {
             show_animations: true,
             animation_delay: Duration::from_millis(500),
             clear_screen: true,
+            seed: 0,
         }
     }
 
@@ -22,9 +26,20 @@ impl GameVisualizer {
             show_animations,
             animation_delay: Duration::from_millis(animation_delay_ms),
             clear_screen,
+            seed: 0,
         }
     }
 
+    /// Seeds the decorative randomness of the animations.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn animation_delay(&self) -> Duration {
+        self.animation_delay
+    }
+
     pub fn display_game_header(&self) {
         if self.clear_screen {
             self.clear_screen_cmd();
@@ -37,20 +52,24 @@ impl GameVisualizer {
     }
 
     pub fn display_game_state(&self, game: &GameState, piece: &Piece, player1_name: &str, player2_name: &str) {
-        let (p1_score, p2_score) = game.calculate_scores();
+        let names = [player1_name, player2_name];
+        let scores = game.player_scores();
         
         println!("Turn: {} | Current Player: {} ({})", 
                  game.turn,
-                 if game.current_player == 1 { player1_name } else { player2_name },
-                 if game.current_player == 1 { "@" } else { "$" });
+                 player_name(&names, game.current_player),
+                 crate::Cell::old_for(game.current_player));
         
-        println!("Scores: {} {} | {} {}", 
-                 player1_name, p1_score, 
-                 player2_name, p2_score);
+        let score_line: Vec<String> = scores
+            .iter()
+            .enumerate()
+            .map(|(index, score)| format!("{} {}", player_name(&names, index as u8 + 1), score))
+            .collect();
+        println!("Scores: {}", score_line.join(" | "));
         
         // Progress bar
-        let total_cells = game.width * game.height;
-        let filled_cells = (p1_score + p2_score) as usize;
+        let total_cells = game.playable_cells();
+        let filled_cells = scores.iter().sum::<u32>() as usize;
         let progress = (filled_cells as f32 / total_cells as f32 * 40.0) as usize;
         
         print!("Progress: [");
@@ -87,6 +106,9 @@ impl GameVisualizer {
             }
             println!();
         }
+        if game.topology == Topology::Torus {
+            println!("   ↺ edges wrap around: pieces continue on the opposite side");
+        }
         
         // Display current piece if provided
         if let Some(p) = piece {
@@ -112,35 +134,38 @@ impl GameVisualizer {
         println!("                        GAME OVER                        ");
         println!("{}", "═".repeat(60));
         
-        let (p1_score, p2_score) = game.calculate_scores();
+        let names = [player1_name, player2_name];
+        let scores = game.player_scores();
         
         println!("Final Scores:");
-        println!("{}: {} points", player1_name, p1_score);
-        println!("{}: {} points", player2_name, p2_score);
-        
-        let total_cells = game.width * game.height;
-        let p1_percentage = (p1_score as f32 / total_cells as f32) * 100.0;
-        let p2_percentage = (p2_score as f32 / total_cells as f32) * 100.0;
+        for (index, score) in scores.iter().enumerate() {
+            println!("{}: {} points", player_name(&names, index as u8 + 1), score);
+        }
+        if game.is_team_game() {
+            for (index, score) in game.team_scores().iter().enumerate() {
+                println!("Team {}: {} points", index + 1, score);
+            }
+        }
         
+        let total_cells = game.playable_cells() as f32;
         println!("\nTerritory Control:");
-        println!("{}: {:.1}%", player1_name, p1_percentage);
-        println!("{}: {:.1}%", player2_name, p2_percentage);
+        for (index, &score) in scores.iter().enumerate() {
+            println!("{}: {:.1}%", player_name(&names, index as u8 + 1), score as f32 / total_cells * 100.0);
+        }
         
-        match game.winner {
-            Some(1) => {
-                println!("\n🎉 {} WINS! 🎉", player1_name);
-                self.display_victory_animation();
-            }
-            Some(2) => {
-                println!("\n🎉 {} WINS! 🎉", player2_name);
+        let winner = if game.is_team_game() {
+            game.winning_team.map(|team| format!("Team {}", team))
+        } else {
+            game.winner.map(|player| player_name(&names, player))
+        };
+        match winner {
+            Some(winner) => {
+                println!("\n🎉 {} WINS! 🎉", winner);
                 self.display_victory_animation();
             }
             None => {
                 println!("\n🤝 IT'S A TIE! 🤝");
             }
-            _ => {
-                println!("\n🤝 IT'S A TIE! 🤝");
-            }
         }
         
         println!("\nFinal Board:");
@@ -167,6 +192,27 @@ impl GameVisualizer {
         }
     }
 
+    /// Plays a replay through from the start, one move per second.
+    pub fn play_replay(&self, replay: &Replay) -> io::Result<()> {
+        let states = replay.states()?;
+        println!("🎬 Starting game replay ({} moves)", replay.moves.len());
+
+        for (index, replay_move) in replay.moves.iter().enumerate() {
+            std::thread::sleep(Duration::from_millis(1000));
+            self.display_game_header();
+            println!("Replay Move: {}/{}", index + 1, replay.moves.len());
+            println!("Player: {}", replay_move.player);
+            match replay_move.position {
+                Some((x, y)) => println!("Move: ({}, {})", y, x),
+                None => println!("Move: No valid moves"),
+            }
+            self.display_board_with_piece(&states[index + 1], Some(&replay_move.piece));
+        }
+
+        println!("📽️ Replay finished!");
+        Ok(())
+    }
+
     pub fn wait_for_input(&self, message: &str) {
         print!("{}", message);
         io::stdout().flush().unwrap();
@@ -213,12 +259,11 @@ impl GameVisualizer {
         }
         
         let fireworks = ["🎆", "🎇", "✨", "🌟", "💫"];
+        let mut rng = StdRng::seed_from_u64(self.seed);
         
         for _ in 0..5 {
             print!("   ");
             for _ in 0..10 {
-                use rand::Rng;
-                let mut rng = rand::thread_rng();
                 let firework = fireworks[rng.gen_range(0..fireworks.len())];
                 print!("{} ", firework);
             }
@@ -238,112 +283,16 @@ impl GameVisualizer {
     }
 }
 
-pub struct GameReplay {
-    moves: Vec<ReplayMove>,
-    current_move: usize,
-}
-
-#[derive(Debug, Clone)]
-pub struct ReplayMove {
-    pub player: u8,
-    pub piece: Piece,
-    pub position: Option<(usize, usize)>,
-    pub game_state: GameState,
-    pub timestamp: Instant,
-}
-
-impl GameReplay {
-    pub fn new() -> Self {
-        Self {
-            moves: Vec::new(),
-            current_move: 0,
-        }
-    }
-
-    pub fn add_move(&mut self, player: u8, piece: Piece, position: Option<(usize, usize)>, game_state: GameState) {
-        self.moves.push(ReplayMove {
-            player,
-            piece,
-            position,
-            game_state,
-            timestamp: Instant::now(),
-        });
-    }
-
-    pub fn play_replay(&mut self, visualizer: &GameVisualizer) {
-        println!("🎬 Starting game replay ({} moves)", self.moves.len());
-        println!("Controls: Space=play/pause, ←/→=step, R=restart, Q=quit");
-        
-        self.current_move = 0;
-        let mut paused = true;
-        
-        loop {
-            if self.current_move >= self.moves.len() {
-                println!("📽️ Replay finished!");
-                break;
-            }
-            
-            let replay_move = &self.moves[self.current_move];
-            
-            visualizer.display_game_header();
-            println!("Replay Move: {}/{}", self.current_move + 1, self.moves.len());
-            println!("Player: {}", replay_move.player);
-            
-            if let Some((x, y)) = replay_move.position {
-                println!("Move: ({}, {})", y, x);
-            } else {
-                println!("Move: No valid moves");
-            }
-            
-            visualizer.display_board_with_piece(&replay_move.game_state, Some(&replay_move.piece));
-            
-            if paused {
-                println!("\n⏸️  PAUSED - Press Space to continue");
-            } else {
-                println!("\n▶️  PLAYING - Press Space to pause");
-                std::thread::sleep(Duration::from_millis(1000));
-                self.current_move += 1;
-                continue;
-            }
-            
-            // Handle input
-            match self.get_replay_input() {
-                ReplayCommand::PlayPause => paused = !paused,
-                ReplayCommand::StepForward => {
-                    self.current_move = (self.current_move + 1).min(self.moves.len() - 1);
-                }
-                ReplayCommand::StepBackward => {
-                    self.current_move = self.current_move.saturating_sub(1);
-                }
-                ReplayCommand::Restart => {
-                    self.current_move = 0;
-                }
-                ReplayCommand::Quit => break,
-            }
-        }
-    }
-
-    fn get_replay_input(&self) -> ReplayCommand {
-        use std::io::Read;
-        
-        let mut buffer = [0; 1];
-        io::stdin().read_exact(&mut buffer).unwrap_or_default();
-        
-        match buffer[0] {
-            b' ' => ReplayCommand::PlayPause,
-            b'a' | b'h' => ReplayCommand::StepBackward,
-            b'd' | b'l' => ReplayCommand::StepForward,
-            b'r' => ReplayCommand::Restart,
-            b'q' => ReplayCommand::Quit,
-            _ => ReplayCommand::PlayPause,
-        }
+impl Default for GameVisualizer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-enum ReplayCommand {
-    PlayPause,
-    StepForward,
-    StepBackward,
-    Restart,
-    Quit,
+/// The name of `player`: one of `names` for the first seats, "Player N"
+/// for the seats after them.
+fn player_name(names: &[&str], player: u8) -> String {
+    names
+        .get(player as usize - 1)
+        .map_or_else(|| format!("Player {}", player), |name| name.to_string())
 }
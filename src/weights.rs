@@ -0,0 +1,196 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Coefficients used by the built-in evaluation functions.
+///
+/// `MinimaxAI` reads `score_diff`, `center_control` and `compactness`;
+/// `GreedyAI` reads `piece_size`, `center_distance` and `blocking`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalWeights {
+    pub score_diff: i32,
+    pub center_control: i32,
+    pub compactness: i32,
+    pub piece_size: i32,
+    pub center_distance: i32,
+    pub blocking: i32,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            score_diff: 100,
+            center_control: 1,
+            compactness: 1,
+            piece_size: 10,
+            center_distance: 1,
+            blocking: 5,
+        }
+    }
+}
+
+impl EvalWeights {
+    pub const NAMES: [&'static str; 6] = [
+        "score_diff",
+        "center_control",
+        "compactness",
+        "piece_size",
+        "center_distance",
+        "blocking",
+    ];
+
+    pub fn get(&self, name: &str) -> Option<i32> {
+        match name {
+            "score_diff" => Some(self.score_diff),
+            "center_control" => Some(self.center_control),
+            "compactness" => Some(self.compactness),
+            "piece_size" => Some(self.piece_size),
+            "center_distance" => Some(self.center_distance),
+            "blocking" => Some(self.blocking),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: i32) -> bool {
+        let slot = match name {
+            "score_diff" => &mut self.score_diff,
+            "center_control" => &mut self.center_control,
+            "compactness" => &mut self.compactness,
+            "piece_size" => &mut self.piece_size,
+            "center_distance" => &mut self.center_distance,
+            "blocking" => &mut self.blocking,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
+    /// Applies a `name=value` override, as given on the command line.
+    pub fn apply_override(&mut self, assignment: &str) -> io::Result<()> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("expected name=value, got '{}'", assignment)))?;
+        let value = value
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| invalid(format!("invalid value for '{}': '{}'", name.trim(), value.trim())))?;
+        if !self.set(name.trim(), value) {
+            return Err(invalid(format!("unknown weight '{}'", name.trim())));
+        }
+        Ok(())
+    }
+
+    pub fn to_config_string(&self) -> String {
+        let mut result = String::new();
+        for name in Self::NAMES {
+            result.push_str(&format!("{} = {}\n", name, self.get(name).unwrap_or(0)));
+        }
+        result
+    }
+}
+
+/// A set of weights with optional per-board-size overrides.
+///
+/// The file format is a small TOML subset: `name = value` lines, `#`
+/// comments, and `[WIDTHxHEIGHT]` sections. Keys before the first section
+/// form the default set; each section starts from the default set and
+/// overrides only the keys it lists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeightsConfig {
+    pub default: EvalWeights,
+    pub by_size: Vec<((usize, usize), EvalWeights)>,
+}
+
+impl WeightsConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_config_string())
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut config = WeightsConfig::default();
+        let mut current_section: Option<(usize, usize)> = None;
+
+        for (line_no, raw_line) in text.lines().enumerate() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let size = parse_size(section.trim()).ok_or_else(|| {
+                    invalid(format!("line {}: invalid section '[{}]', expected [WIDTHxHEIGHT]", line_no + 1, section))
+                })?;
+                if !config.by_size.iter().any(|(existing, _)| *existing == size) {
+                    config.by_size.push((size, config.default.clone()));
+                }
+                current_section = Some(size);
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("line {}: expected name = value", line_no + 1)))?;
+            let name = name.trim();
+            let value = value
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| invalid(format!("line {}: invalid value for '{}'", line_no + 1, name)))?;
+
+            if config.default.get(name).is_none() {
+                return Err(invalid(format!("line {}: unknown weight '{}'", line_no + 1, name)));
+            }
+
+            let weights = match current_section {
+                Some(size) => config
+                    .by_size
+                    .iter_mut()
+                    .find(|(existing, _)| *existing == size)
+                    .map(|(_, weights)| weights)
+                    .expect("section registered when its header was read"),
+                None => &mut config.default,
+            };
+            weights.set(name, value);
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the weights tuned for this board size, or the default set.
+    pub fn for_board(&self, width: usize, height: usize) -> &EvalWeights {
+        self.by_size
+            .iter()
+            .find(|(size, _)| *size == (width, height))
+            .map(|(_, weights)| weights)
+            .unwrap_or(&self.default)
+    }
+
+    pub fn set_for_size(&mut self, width: usize, height: usize, weights: EvalWeights) {
+        match self.by_size.iter_mut().find(|(size, _)| *size == (width, height)) {
+            Some((_, existing)) => *existing = weights,
+            None => self.by_size.push(((width, height), weights)),
+        }
+    }
+
+    pub fn to_config_string(&self) -> String {
+        let mut result = self.default.to_config_string();
+        for ((width, height), weights) in &self.by_size {
+            result.push_str(&format!("\n[{}x{}]\n", width, height));
+            result.push_str(&weights.to_config_string());
+        }
+        result
+    }
+}
+
+fn parse_size(text: &str) -> Option<(usize, usize)> {
+    let (width, height) = text.split_once('x')?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}